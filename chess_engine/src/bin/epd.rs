@@ -0,0 +1,115 @@
+use std::{sync::Arc, time::Duration};
+
+use chess_engine::{
+    epd::{load_epd_suite, run_suite},
+    evaluator::{
+        params::EvalParams,
+        rollout::{LeafEvaluation, DEFAULT_ROLLOUT_PLIES},
+        WinProbability,
+    },
+    search::SearchConfig,
+    SearchAlgorithm, SearchPolicy, PUCT_EXPLORATION, UCT_EXPLORATION,
+};
+
+const USAGE: &str = "<suite.epd> [--movetime ms] [--playouts n] [--depth plies] [--rave] [--puct] \
+                     [--rollout static|random|heavy|hybrid] [--threads n] [--weights file] \
+                     [--win-k k] [--json summary.json]";
+
+/// Runs an EPD test suite and reports which positions were solved. MCTS is used unless
+/// `--depth` selects minimax; without a limit every position gets one second.
+fn main() {
+    let args = std::env::args().collect::<Vec<_>>();
+    if args.len() < 2 {
+        usage(&args[0]);
+    }
+
+    let mut config = SearchConfig::default().with_threads(1);
+    let mut json = None;
+
+    let mut options = args[2..].iter();
+    while let Some(option) = options.next() {
+        let mut value = || options.next().unwrap_or_else(|| usage(&args[0]));
+        match option.as_str() {
+            "--movetime" => {
+                let millis = value().parse().expect("Invalid movetime");
+                config = config.with_movetime(Duration::from_millis(millis));
+            }
+            "--playouts" => {
+                config = config.with_playouts(value().parse().expect("Invalid playouts"))
+            }
+            "--depth" => {
+                let depth = value().parse().expect("Invalid depth");
+                config = config.with_algorithm(SearchAlgorithm::MiniMax { depth });
+            }
+            "--rave" => {
+                config = config.with_algorithm(SearchAlgorithm::Mcts(SearchPolicy::Rave {
+                    exploration: UCT_EXPLORATION,
+                    equivalence: 1000.0,
+                }))
+            }
+            "--puct" => {
+                config = config.with_algorithm(SearchAlgorithm::Mcts(SearchPolicy::Puct {
+                    exploration: PUCT_EXPLORATION,
+                }))
+            }
+            "--rollout" => {
+                let leaf = LeafEvaluation::named(value(), DEFAULT_ROLLOUT_PLIES)
+                    .unwrap_or_else(|| usage(&args[0]));
+                config = config.with_leaf_evaluation(leaf)
+            }
+            "--threads" => {
+                config = config.with_threads(value().parse().expect("Invalid thread count"))
+            }
+            "--weights" => {
+                let params = EvalParams::load(value()).expect("Could not read weights");
+                config = config.with_params(Arc::new(params));
+            }
+            "--win-k" => {
+                let k = value().parse().expect("Invalid K");
+                config = config.with_win_probability(WinProbability::new(k))
+            }
+            "--json" => json = Some(value().clone()),
+            _ => usage(&args[0]),
+        }
+    }
+
+    if config.limits.is_unlimited() {
+        config = config.with_movetime(Duration::from_secs(1));
+    }
+
+    let mut skipped = 0;
+    let suite = load_epd_suite(&args[1], |number, line| {
+        eprintln!("skipped line {}: {}", number, line);
+        skipped += 1;
+    })
+    .expect("Could not read suite");
+    println!("positions: {}, skipped: {}", suite.len(), skipped);
+
+    let summary = run_suite(&suite, &config, |result| {
+        println!(
+            "{} {:<12} {:>6} ms  {}  {}",
+            if result.solved { "solved" } else { "FAILED" },
+            result.id.as_deref().unwrap_or("-"),
+            result.millis,
+            result.found.as_deref().unwrap_or("none"),
+            result.fen,
+        );
+    });
+
+    println!(
+        "solved {}/{} in {:.1} s",
+        summary.solved,
+        summary.total,
+        summary.millis as f64 / 1000.0
+    );
+
+    if let Some(path) = json {
+        summary.save(&path).expect("Could not write summary");
+        println!("summary written to {}", path);
+    }
+}
+
+fn usage(program: &str) -> ! {
+    eprintln!("usage: {} {}", program, USAGE);
+    std::process::exit(1);
+}
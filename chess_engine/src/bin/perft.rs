@@ -0,0 +1,41 @@
+use std::{str::FromStr, time::Instant};
+
+use chess::Board;
+use chess_engine::perft::divide;
+
+/// Usage: `perft <depth> [threads] [fen]`, printing the node count below every move.
+fn main() {
+    let args = std::env::args().collect::<Vec<_>>();
+    if args.len() < 2 {
+        eprintln!("usage: {} <depth> [threads] [fen]", args[0]);
+        std::process::exit(1);
+    }
+
+    let depth = args[1].parse::<usize>().expect("Invalid depth");
+    let threads = args
+        .get(2)
+        .map(|n| n.parse::<usize>().expect("Invalid thread count"))
+        .unwrap_or(1);
+    let board = match args.len() > 3 {
+        true => Board::from_str(&args[3..].join(" ")).expect("Invalid FEN"),
+        false => Board::default(),
+    };
+
+    let start = Instant::now();
+    let moves = divide(&board, depth, threads);
+    let elapsed = start.elapsed();
+
+    for (mov, nodes) in &moves {
+        println!("{}: {}", mov, nodes);
+    }
+
+    let nodes = moves.iter().map(|(_, nodes)| nodes).sum::<u64>();
+    println!();
+    println!("moves: {}", moves.len());
+    println!("nodes: {}", nodes);
+    println!(
+        "time: {} ms, {:.0} nodes/s",
+        elapsed.as_millis(),
+        nodes as f64 / elapsed.as_secs_f64()
+    );
+}
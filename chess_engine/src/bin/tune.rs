@@ -0,0 +1,50 @@
+use chess_engine::{
+    evaluator::params::EvalParams,
+    tuner::{find_k, load_epd, mean_squared_error, tune},
+};
+
+/// Usage: `tune <positions.epd> <output weights> [start weights] [max iterations]`
+fn main() {
+    let args = std::env::args().collect::<Vec<_>>();
+    if args.len() < 3 {
+        eprintln!(
+            "usage: {} <positions.epd> <output weights> [start weights] [max iterations]",
+            args[0]
+        );
+        std::process::exit(1);
+    }
+
+    let positions = load_epd(&args[1]).expect("Could not read positions");
+    let output = &args[2];
+    let params = match args.get(3) {
+        Some(path) => EvalParams::load(path).expect("Could not read start weights"),
+        None => EvalParams::default(),
+    };
+    let max_iterations = args
+        .get(4)
+        .map(|n| n.parse::<usize>().expect("Invalid iteration count"))
+        .unwrap_or(100);
+
+    println!("positions: {}", positions.len());
+    let k = find_k(&positions, &params);
+    println!(
+        "k: {:.4}, error: {:.6}",
+        k,
+        mean_squared_error(&positions, &params, k)
+    );
+
+    let tuned = tune(
+        &positions,
+        &params,
+        k,
+        &EvalParams::steps(1.0),
+        max_iterations,
+        |iteration, error, params| {
+            println!("iteration {}: error {:.6}", iteration, error);
+            params.save(output).expect("Could not write weights");
+        },
+    );
+
+    tuned.save(output).expect("Could not write weights");
+    println!("weights written to {}", output);
+}
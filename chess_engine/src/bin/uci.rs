@@ -0,0 +1,18 @@
+use std::io::BufRead;
+
+/// Speaks UCI or XBoard, depending on the first command the interface sends.
+fn main() {
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines().map_while(Result::ok);
+
+    let first = match lines.by_ref().find(|line| !line.trim().is_empty()) {
+        Some(first) => first,
+        None => return,
+    };
+
+    if first.trim() == "xboard" {
+        chess_engine::xboard::run(std::iter::once(first).chain(lines));
+    } else {
+        chess_engine::uci::run(std::iter::once(first).chain(lines));
+    }
+}
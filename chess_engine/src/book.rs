@@ -0,0 +1,232 @@
+//! Polyglot opening books.
+//!
+//! A `.bin` book is a list of 16 byte big endian entries sorted by key: the Polyglot
+//! hash of the position, the move, its weight and a learning value that is ignored.
+
+use std::{fs, io, path::Path};
+
+use chess::{BitBoard, Board, ChessMove, Color, File, MoveGen, Piece, Rank, Square, EMPTY};
+use rand::Rng;
+
+use random::RANDOM64;
+
+mod random;
+
+const ENTRY_SIZE: usize = 16;
+
+/// Polyglot hash of `board`. It differs from [`Board::get_hash`], but is the key that
+/// Polyglot books are indexed by.
+pub fn polyglot_hash(board: &Board) -> u64 {
+    let mut hash = 0;
+
+    for square in *board.combined() {
+        let piece = board.piece_on(square).unwrap();
+        let color = board.color_on(square).unwrap();
+        let kind = piece.to_index() * 2 + usize::from(color == Color::White);
+        hash ^= RANDOM64[64 * kind + square.to_index()];
+    }
+
+    for (offset, color) in [(768, Color::White), (770, Color::Black)] {
+        let rights = board.castle_rights(color);
+        if rights.has_kingside() {
+            hash ^= RANDOM64[offset];
+        }
+        if rights.has_queenside() {
+            hash ^= RANDOM64[offset + 1];
+        }
+    }
+
+    // The en passant file only counts when a pawn stands next to the one that just
+    // moved, whether or not the capture is legal.
+    if let Some(square) = board.en_passant() {
+        let side = board.side_to_move();
+        let rank = match side {
+            Color::White => Rank::Fifth,
+            Color::Black => Rank::Fourth,
+        };
+        let file = square.get_file().to_index();
+        let capturers = board.pieces(Piece::Pawn) & board.color_combined(side);
+
+        let can_capture = [file.wrapping_sub(1), file + 1]
+            .iter()
+            .filter(|&&f| f < 8)
+            .any(|&f| {
+                let from = Square::make_square(rank, File::from_index(f));
+                capturers & BitBoard::from_square(from) != EMPTY
+            });
+
+        if can_capture {
+            hash ^= RANDOM64[772 + file];
+        }
+    }
+
+    if board.side_to_move() == Color::White {
+        hash ^= RANDOM64[780];
+    }
+
+    hash
+}
+
+/// One book entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BookEntry {
+    pub key: u64,
+    /// The move as Polyglot encodes it, see [`BookEntry::chess_move`].
+    pub mov: u16,
+    pub weight: u16,
+    pub learn: u32,
+}
+
+impl BookEntry {
+    /// Decodes the move for `board`. Polyglot writes castling as the king capturing its
+    /// own rook, which is translated to the king's two square step.
+    pub fn chess_move(&self, board: &Board) -> Option<ChessMove> {
+        let square = |bits: u16| {
+            let file = File::from_index((bits & 7) as usize);
+            let rank = Rank::from_index(((bits >> 3) & 7) as usize);
+            Square::make_square(rank, file)
+        };
+
+        let source = square(self.mov >> 6);
+        let mut dest = square(self.mov);
+        let promotion = match (self.mov >> 12) & 7 {
+            0 => None,
+            1 => Some(Piece::Knight),
+            2 => Some(Piece::Bishop),
+            3 => Some(Piece::Rook),
+            4 => Some(Piece::Queen),
+            _ => return None,
+        };
+
+        let color = board.side_to_move();
+        if board.piece_on(source) == Some(Piece::King)
+            && board.piece_on(dest) == Some(Piece::Rook)
+            && board.color_on(dest) == Some(color)
+        {
+            let file = if dest.get_file().to_index() > source.get_file().to_index() {
+                File::G
+            } else {
+                File::C
+            };
+            dest = Square::make_square(source.get_rank(), file);
+        }
+
+        let mov = ChessMove::new(source, dest, promotion);
+        MoveGen::new_legal(board)
+            .any(|legal| legal == mov)
+            .then_some(mov)
+    }
+}
+
+/// A Polyglot book loaded into memory.
+#[derive(Debug, Clone, Default)]
+pub struct OpeningBook {
+    entries: Vec<BookEntry>,
+    deterministic: bool,
+}
+
+impl OpeningBook {
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        if !bytes.len().is_multiple_of(ENTRY_SIZE) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "book size is not a multiple of 16 bytes",
+            ));
+        }
+
+        let mut entries = bytes
+            .chunks_exact(ENTRY_SIZE)
+            .map(|entry| BookEntry {
+                key: u64::from_be_bytes(entry[0..8].try_into().unwrap()),
+                mov: u16::from_be_bytes(entry[8..10].try_into().unwrap()),
+                weight: u16::from_be_bytes(entry[10..12].try_into().unwrap()),
+                learn: u32::from_be_bytes(entry[12..16].try_into().unwrap()),
+            })
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|entry| entry.key);
+
+        Ok(Self {
+            entries,
+            deterministic: false,
+        })
+    }
+
+    /// Always play the move with the highest weight instead of picking one at random in
+    /// proportion to the weights.
+    pub fn deterministic(mut self, deterministic: bool) -> Self {
+        self.deterministic = deterministic;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// All entries for `board`, in file order.
+    pub fn entries(&self, board: &Board) -> &[BookEntry] {
+        let key = polyglot_hash(board);
+        let start = self.entries.partition_point(|entry| entry.key < key);
+        let end = self.entries.partition_point(|entry| entry.key <= key);
+
+        &self.entries[start..end]
+    }
+
+    /// The legal book moves for `board` with their weights.
+    pub fn moves(&self, board: &Board) -> Vec<(ChessMove, u16)> {
+        self.entries(board)
+            .iter()
+            .filter_map(|entry| Some((entry.chess_move(board)?, entry.weight)))
+            .collect()
+    }
+
+    /// Picks a book move for `board`, see [`OpeningBook::probe_with_rng`].
+    pub fn probe(&self, board: &Board) -> Option<(ChessMove, u16)> {
+        self.probe_with_rng(board, &mut rand::thread_rng())
+    }
+
+    /// Picks a book move for `board` in proportion to the weights, or the heaviest one
+    /// when the book is deterministic. Moves with weight zero are never played.
+    pub fn probe_with_rng<R: Rng + ?Sized>(
+        &self,
+        board: &Board,
+        rng: &mut R,
+    ) -> Option<(ChessMove, u16)> {
+        let moves = self
+            .moves(board)
+            .into_iter()
+            .filter(|(_, weight)| *weight > 0)
+            .collect::<Vec<_>>();
+
+        if self.deterministic {
+            // The first of equally heavy moves wins.
+            return moves
+                .iter()
+                .rev()
+                .max_by_key(|(_, weight)| *weight)
+                .copied();
+        }
+
+        let total = moves.iter().map(|(_, weight)| *weight as u32).sum::<u32>();
+        if total == 0 {
+            return None;
+        }
+
+        let mut choice = rng.gen_range(0..total);
+        moves.into_iter().find(|(_, weight)| {
+            if choice < *weight as u32 {
+                true
+            } else {
+                choice -= *weight as u32;
+                false
+            }
+        })
+    }
+}
@@ -0,0 +1,169 @@
+//! Tactical test suites in EPD, such as WAC or ECM: every position names the moves to
+//! find (`bm`) or to avoid (`am`), and the engine is run on each of them under a limit.
+
+use std::{
+    fs, io,
+    path::Path,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use chess::{Board, ChessMove, Game};
+use serde::{Deserialize, Serialize};
+
+use crate::{find_move, search::SearchConfig};
+
+/// A test position with the moves that solve it.
+#[derive(Debug, Clone)]
+pub struct EpdPosition {
+    pub id: Option<String>,
+    pub board: Board,
+    /// Any of these solves the position (`bm`).
+    pub best_moves: Vec<ChessMove>,
+    /// None of these may be played (`am`).
+    pub avoid_moves: Vec<ChessMove>,
+}
+
+impl EpdPosition {
+    pub fn is_solved(&self, mov: ChessMove) -> bool {
+        (self.best_moves.is_empty() || self.best_moves.contains(&mov))
+            && !self.avoid_moves.contains(&mov)
+    }
+}
+
+/// Outcome of the engine on one position of a suite.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PositionResult {
+    pub id: Option<String>,
+    pub fen: String,
+    /// The move the engine played, in UCI notation.
+    pub found: Option<String>,
+    pub solved: bool,
+    pub millis: u64,
+}
+
+/// Results of a whole suite, as written to the JSON summary.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SuiteSummary {
+    pub total: usize,
+    pub solved: usize,
+    pub millis: u64,
+    pub positions: Vec<PositionResult>,
+}
+
+impl SuiteSummary {
+    pub fn new(positions: Vec<PositionResult>) -> Self {
+        SuiteSummary {
+            total: positions.len(),
+            solved: positions.iter().filter(|result| result.solved).count(),
+            millis: positions.iter().map(|result| result.millis).sum(),
+            positions,
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let text = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+
+        fs::write(path, text)
+    }
+}
+
+/// Parses an EPD line with `bm`, `am` and `id` operations. Moves are given in SAN; a
+/// line without any `bm` or `am` move, or with a move that is illegal, is rejected.
+pub fn parse_epd_position(line: &str) -> Option<EpdPosition> {
+    let tokens = line.split_whitespace().collect::<Vec<_>>();
+    if tokens.len() < 4 {
+        return None;
+    }
+
+    let board = Board::from_str(&format!("{} 0 1", tokens[..4].join(" "))).ok()?;
+    let operations = tokens[4..].join(" ");
+
+    let mut position = EpdPosition {
+        id: None,
+        board,
+        best_moves: Vec::new(),
+        avoid_moves: Vec::new(),
+    };
+
+    for operation in operations.split(';').map(str::trim) {
+        let (opcode, operands) = operation.split_once(' ').unwrap_or((operation, ""));
+        match opcode {
+            "bm" => position.best_moves = parse_moves(&board, operands)?,
+            "am" => position.avoid_moves = parse_moves(&board, operands)?,
+            "id" => position.id = Some(operands.trim().trim_matches('"').to_string()),
+            _ => {}
+        }
+    }
+
+    match position.best_moves.is_empty() && position.avoid_moves.is_empty() {
+        true => None,
+        false => Some(position),
+    }
+}
+
+fn parse_moves(board: &Board, operands: &str) -> Option<Vec<ChessMove>> {
+    operands
+        .split_whitespace()
+        .map(|san| san.trim_end_matches(['+', '#', '!', '?']))
+        .map(|san| ChessMove::from_san(board, san).ok())
+        .collect()
+}
+
+/// Loads every position in `path` that has moves to find or avoid. Every other line that
+/// isn't blank is passed to `on_skipped` along with its line number, counted from 1.
+pub fn load_epd_suite<F>(path: impl AsRef<Path>, mut on_skipped: F) -> io::Result<Vec<EpdPosition>>
+where
+    F: FnMut(usize, &str),
+{
+    let text = fs::read_to_string(path)?;
+
+    Ok(text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .filter_map(|(index, line)| {
+            let position = parse_epd_position(line);
+            if position.is_none() {
+                on_skipped(index + 1, line);
+            }
+            position
+        })
+        .collect())
+}
+
+/// Searches `position` with [`find_move`] and checks the move it settles on.
+pub fn run_position(position: &EpdPosition, config: &SearchConfig) -> PositionResult {
+    let game = Game::new_with_board(position.board);
+    let start = Instant::now();
+    let found = find_move(&game, config).best_move;
+
+    PositionResult {
+        id: position.id.clone(),
+        fen: position.board.to_string(),
+        found: found.map(|mov| mov.to_string()),
+        solved: found.is_some_and(|mov| position.is_solved(mov)),
+        millis: duration_millis(start.elapsed()),
+    }
+}
+
+/// Runs every position of `suite`, calling `on_result` after each one.
+pub fn run_suite<F>(suite: &[EpdPosition], config: &SearchConfig, mut on_result: F) -> SuiteSummary
+where
+    F: FnMut(&PositionResult),
+{
+    let results = suite
+        .iter()
+        .map(|position| {
+            let result = run_position(position, config);
+            on_result(&result);
+            result
+        })
+        .collect();
+
+    SuiteSummary::new(results)
+}
+
+fn duration_millis(duration: Duration) -> u64 {
+    duration.as_millis().try_into().unwrap_or(u64::MAX)
+}
@@ -1,21 +1,312 @@
-use std::ops::Not;
+use std::{io, ops::Not, path::Path, sync::Arc};
 
 use chess::{Board, Color, Piece};
 use mcts::{Evaluator, GameState};
+use search_tree::GameEvaluator;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "nnue")]
+use crate::nnue::{Network, NnueEvaluator};
+use crate::{
+    evaluator::{
+        params::EvalParams,
+        pawns::PawnTable,
+        rollout::{LeafEvaluation, RolloutPolicy, ROLLOUT_WIN},
+    },
+    mcts_handler::{ChessMCTS, ChessPuctMCTS, ChessRaveMCTS},
+    state::{DrawReason, GameWrapper, Position},
+    tablebase::{self, Tablebase},
+    tree_search::ChessGame,
+};
+
+pub mod endgame;
+pub mod king_safety;
+pub mod mobility;
+pub mod params;
+pub mod pawns;
+pub mod priors;
+pub mod rollout;
+
+/// Static evaluation from the point of view of `color`.
+pub struct ChessEvaluator {
+    pub color: Color,
+    params: Arc<EvalParams>,
+    pawn_table: PawnTable,
+    tablebase: Option<Arc<dyn Tablebase>>,
+    leaf: LeafEvaluation,
+    win_probability: WinProbability,
+    /// Replaces the handcrafted terms of [`ChessEvaluator::evaluate_board`].
+    #[cfg(feature = "nnue")]
+    nnue: Option<NnueEvaluator>,
+}
+
+/// Weights of the mobility and king-safety terms, in centipawns.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EvalWeights {
+    /// Per safe square a piece attacks.
+    pub knight_mobility: f32,
+    pub bishop_mobility: f32,
+    pub rook_mobility: f32,
+    pub queen_mobility: f32,
+    /// Per own pawn on the two ranks in front of the king.
+    pub pawn_shield: f32,
+    /// Per file on or next to the king without any pawns.
+    pub open_file: f32,
+    /// Per file on or next to the king without own pawns.
+    pub semi_open_file: f32,
+    /// Per enemy piece attacking the king zone.
+    pub king_attackers: f32,
+    /// Per attack of an enemy piece on a king-zone square.
+    pub king_zone_attacks: f32,
+    /// In won endings, per step the enemy king has been pushed towards the edge.
+    pub mop_up_edge: f32,
+    /// In won endings, per step the kings are closer than opposite corners.
+    pub mop_up_kings: f32,
+}
+
+impl Default for EvalWeights {
+    fn default() -> Self {
+        Self {
+            knight_mobility: 4.0,
+            bishop_mobility: 5.0,
+            rook_mobility: 2.0,
+            queen_mobility: 1.0,
+            pawn_shield: 10.0,
+            open_file: -20.0,
+            semi_open_file: -10.0,
+            king_attackers: -8.0,
+            king_zone_attacks: -4.0,
+            mop_up_edge: 10.0,
+            mop_up_kings: 15.0,
+        }
+    }
+}
 
-use crate::{mcts_handler::ChessMCTS, state::GameWrapper};
+/// `K` of the [`WinProbability`] curve when none is configured.
+pub const DEFAULT_WIN_PROBABILITY_K: f64 = 1.0;
+/// Win probabilities are kept this far from 0 and 1 when turned back into centipawns.
+const PROBABILITY_MARGIN: f64 = 1e-6;
+
+/// Logistic curve from centipawns to the probability of winning, the same curve the
+/// tuner fits `K` for (see [`tuner::sigmoid`](crate::tuner::sigmoid)). MCTS backs up
+/// these probabilities, so static evaluations and proven results share one range.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WinProbability {
+    pub k: f64,
+}
 
-pub struct ChessEvaluator(pub Color);
+impl Default for WinProbability {
+    fn default() -> Self {
+        WinProbability {
+            k: DEFAULT_WIN_PROBABILITY_K,
+        }
+    }
+}
 
-pub const SCALE: f64 = 1e7;
+impl WinProbability {
+    pub fn new(k: f64) -> Self {
+        assert!(k > 0.0, "K must be positive");
+        WinProbability { k }
+    }
+
+    /// Probability of winning with an evaluation of `centipawns`.
+    pub fn of(&self, centipawns: f64) -> f64 {
+        crate::tuner::sigmoid(centipawns, self.k)
+    }
+
+    /// Evaluation in centipawns with a probability of winning of `probability`; the
+    /// inverse of [`WinProbability::of`].
+    pub fn centipawns(&self, probability: f64) -> f64 {
+        let probability = probability.clamp(PROBABILITY_MARGIN, 1.0 - PROBABILITY_MARGIN);
+        -400.0 / self.k * (1.0 / probability - 1.0).log10()
+    }
+}
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum ChessEvaluation {
     Winner(Color),
-    Draw,
+    Draw(DrawReason),
     Evaluation(f64),
 }
 
+impl ChessEvaluator {
+    pub fn new(color: Color) -> Self {
+        Self::with_params(color, Arc::new(EvalParams::default()))
+    }
+
+    pub fn with_params(color: Color, params: Arc<EvalParams>) -> Self {
+        Self {
+            color,
+            pawn_table: PawnTable::new(1 << 14, params.pawns.clone()),
+            params,
+            tablebase: None,
+            leaf: LeafEvaluation::Static,
+            win_probability: WinProbability::default(),
+            #[cfg(feature = "nnue")]
+            nnue: None,
+        }
+    }
+
+    /// Evaluates with `params` from now on. The pawn table is kept, but forgets the
+    /// structures it scored with the old weights.
+    pub fn set_params(&mut self, params: Arc<EvalParams>) {
+        self.pawn_table.reset(params.pawns.clone());
+        self.params = params;
+    }
+
+    /// Looks positions with few enough pieces up in `tablebase` during search.
+    pub fn with_tablebase(mut self, tablebase: Arc<dyn Tablebase>) -> Self {
+        self.tablebase = Some(tablebase);
+        self
+    }
+
+    /// Turns evaluations into rewards with `win_probability`.
+    pub fn with_win_probability(mut self, win_probability: WinProbability) -> Self {
+        self.win_probability = win_probability;
+        self
+    }
+
+    pub fn win_probability(&self) -> WinProbability {
+        self.win_probability
+    }
+
+    /// Evaluates new leaves of the MCTS tree with `leaf`; minimax always evaluates
+    /// statically.
+    pub fn with_leaf_evaluation(mut self, leaf: LeafEvaluation) -> Self {
+        self.leaf = leaf;
+        self
+    }
+
+    /// Evaluates positions with `network`, as an [`NnueEvaluator`] would, instead of the
+    /// handcrafted terms. The params are still used for the move priors.
+    #[cfg(feature = "nnue")]
+    pub fn with_network(mut self, network: Arc<Network>) -> Self {
+        self.nnue = Some(NnueEvaluator::new(self.color, network));
+        self
+    }
+
+    /// Evaluator using the weight set in `path`, see [`EvalParams::load`].
+    pub fn from_file(color: Color, path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::with_params(color, Arc::new(EvalParams::load(path)?)))
+    }
+
+    pub fn params(&self) -> &Arc<EvalParams> {
+        &self.params
+    }
+
+    /// Static evaluation of `board` in centipawns, ignoring mates and draws.
+    pub fn evaluate_board(&self, board: &Board) -> f64 {
+        #[cfg(feature = "nnue")]
+        if let Some(nnue) = &self.nnue {
+            return nnue.evaluate_board(board);
+        }
+
+        let params = &self.params;
+        let phase = simplified_evaluation_function::game_phase(board);
+        let value = simplified_evaluation_function::board_value(self.color, board, params)
+            + pawns::evaluate(self.color, board, &self.pawn_table)
+            + mobility::evaluate(self.color, board, &params.weights)
+            + king_safety::evaluate(self.color, board, &params.weights, phase)
+            + endgame::evaluate(self.color, board, &params.weights);
+
+        value as f64
+    }
+
+    /// Static evaluation of `position`, from the accumulator it carries when that belongs
+    /// to the evaluator's network.
+    fn evaluate_position(&self, position: &Position) -> f64 {
+        #[cfg(feature = "nnue")]
+        if let Some(nnue) = &self.nnue {
+            return nnue.evaluate_position(position);
+        }
+
+        self.evaluate_board(position.board())
+    }
+
+    fn evaluate_state(&self, state: &GameWrapper) -> ChessEvaluation {
+        self.evaluate_outcome(state.position(), state.outcome())
+    }
+
+    /// Evaluation of `position`, unless `outcome` says the game is over there.
+    fn evaluate_outcome(
+        &self,
+        position: &Position,
+        outcome: Option<ChessEvaluation>,
+    ) -> ChessEvaluation {
+        let tablebase = || {
+            let tablebase = self.tablebase.as_deref()?;
+            tablebase::evaluate(tablebase, position.board())
+        };
+
+        match outcome.or_else(tablebase) {
+            Some(outcome) => outcome,
+            // evaluate_board(self.0, &state.0.current_position(), &moves),
+            None => ChessEvaluation::Evaluation(self.evaluate_position(position)),
+        }
+    }
+
+    /// Evaluation of a new leaf of the MCTS tree, by the configured [`LeafEvaluation`].
+    fn evaluate_leaf(&self, state: &GameWrapper) -> ChessEvaluation {
+        let score = match self.evaluate_state(state) {
+            ChessEvaluation::Evaluation(score) => score,
+            outcome => return outcome,
+        };
+
+        ChessEvaluation::Evaluation(match self.leaf {
+            LeafEvaluation::Static => score,
+            LeafEvaluation::Random { plies } => {
+                self.rollout_score(state, RolloutPolicy::Random, plies)
+            }
+            LeafEvaluation::Heavy { plies } => {
+                self.rollout_score(state, RolloutPolicy::Heavy, plies)
+            }
+            LeafEvaluation::Hybrid {
+                plies,
+                rollout_weight,
+            } => {
+                let rollout = self.rollout_score(state, RolloutPolicy::Heavy, plies);
+                rollout_weight * rollout + (1.0 - rollout_weight) * score
+            }
+        })
+    }
+
+    /// Score in centipawns of a rollout from `state`, mates counting as [`ROLLOUT_WIN`].
+    fn rollout_score(&self, state: &GameWrapper, policy: RolloutPolicy, plies: usize) -> f64 {
+        let (end, _) = rollout::rollout(state.position(), policy, plies, &mut rand::thread_rng());
+
+        match self.evaluate_outcome(&end, end.outcome()) {
+            ChessEvaluation::Evaluation(score) => score,
+            ChessEvaluation::Draw(_) => 0.0,
+            ChessEvaluation::Winner(winner) if winner == self.color => ROLLOUT_WIN,
+            ChessEvaluation::Winner(_) => -ROLLOUT_WIN,
+        }
+    }
+
+    pub fn interpret(&self, evaluation: &ChessEvaluation, player: &Color) -> f64 {
+        interpret_evaluation(self.color, evaluation, player, self.win_probability)
+    }
+}
+
+/// Reward of `evaluation`, made from the point of view of `color`, for `player`: the
+/// probability that `player` wins, exactly 1, 0.5 or 0 for a decided game.
+pub fn interpret_evaluation(
+    color: Color,
+    evaluation: &ChessEvaluation,
+    player: &Color,
+    win_probability: WinProbability,
+) -> f64 {
+    match evaluation {
+        ChessEvaluation::Winner(winner) if winner == player => 1.0,
+        ChessEvaluation::Winner(_) => 0.0,
+        ChessEvaluation::Evaluation(e) => match &color == player {
+            true => win_probability.of(*e),
+            false => win_probability.of(-*e),
+        },
+        ChessEvaluation::Draw(_) => 0.5,
+    }
+}
+
 impl Evaluator<ChessMCTS> for ChessEvaluator {
     type StateEvaluation = ChessEvaluation;
 
@@ -27,26 +318,7 @@ impl Evaluator<ChessMCTS> for ChessEvaluator {
     ) -> (Vec<mcts::MoveEvaluation<ChessMCTS>>, Self::StateEvaluation) {
         let evals = moves.iter().map(|_| ()).collect();
 
-        if state.is_terminal() {
-            let winner = state.get_winner();
-
-            let points = match winner {
-                Some(color) => ChessEvaluation::Winner(color),
-                None => ChessEvaluation::Draw,
-            };
-
-            (evals, points)
-        } else {
-            (
-                evals,
-                // evaluate_board(self.0, &state.0.current_position(), &moves),
-                ChessEvaluation::Evaluation(simplified_evaluation_function::board_value(
-                    self.0,
-                    &state.0.current_position(),
-                    simplified_evaluation_function::GameTime::Middle,
-                ) as f64), // evaluate_rollout(self.0, state.0.current_position(), &moves),
-            )
-        }
+        (evals, self.evaluate_leaf(state))
     }
 
     fn evaluate_existing_state(
@@ -63,21 +335,90 @@ impl Evaluator<ChessMCTS> for ChessEvaluator {
         evaluation: &Self::StateEvaluation,
         player: &mcts::Player<ChessMCTS>,
     ) -> f64 {
-        // println!("player: {:?}, evaluation: {:?}", player,  evaluation);
-        (match evaluation {
-            ChessEvaluation::Winner(winner) if winner == player => 100000.0,
-            ChessEvaluation::Winner(_) => -100000.0,
-            ChessEvaluation::Evaluation(e) => {
-                // println!("E: {}", e);
-                // *e
-                if &self.0 == player {
-                    *e
-                } else {
-                    -*e
-                }
-            }
-            ChessEvaluation::Draw => 0.0,
-        } * SCALE)
+        self.interpret(evaluation, player)
+    }
+}
+
+impl Evaluator<ChessRaveMCTS> for ChessEvaluator {
+    type StateEvaluation = ChessEvaluation;
+
+    fn evaluate_new_state(
+        &self,
+        state: &GameWrapper,
+        moves: &mcts::MoveList<ChessRaveMCTS>,
+        _: Option<mcts::SearchHandle<ChessRaveMCTS>>,
+    ) -> (
+        Vec<mcts::MoveEvaluation<ChessRaveMCTS>>,
+        Self::StateEvaluation,
+    ) {
+        let mover = state.current_player();
+        let evals = moves.iter().map(|_| mover).collect();
+
+        (evals, self.evaluate_leaf(state))
+    }
+
+    fn evaluate_existing_state(
+        &self,
+        _state: &GameWrapper,
+        existing_evaln: &Self::StateEvaluation,
+        _handle: mcts::SearchHandle<ChessRaveMCTS>,
+    ) -> Self::StateEvaluation {
+        existing_evaln.clone()
+    }
+
+    fn interpret_evaluation_for_player(
+        &self,
+        evaluation: &Self::StateEvaluation,
+        player: &mcts::Player<ChessRaveMCTS>,
+    ) -> f64 {
+        self.interpret(evaluation, player)
+    }
+}
+
+impl Evaluator<ChessPuctMCTS> for ChessEvaluator {
+    type StateEvaluation = ChessEvaluation;
+
+    fn evaluate_new_state(
+        &self,
+        state: &GameWrapper,
+        moves: &mcts::MoveList<ChessPuctMCTS>,
+        _: Option<mcts::SearchHandle<ChessPuctMCTS>>,
+    ) -> (
+        Vec<mcts::MoveEvaluation<ChessPuctMCTS>>,
+        Self::StateEvaluation,
+    ) {
+        let evals = priors::move_priors(state.board(), moves, &self.params);
+
+        (evals, self.evaluate_leaf(state))
+    }
+
+    fn evaluate_existing_state(
+        &self,
+        _state: &GameWrapper,
+        existing_evaln: &Self::StateEvaluation,
+        _handle: mcts::SearchHandle<ChessPuctMCTS>,
+    ) -> Self::StateEvaluation {
+        existing_evaln.clone()
+    }
+
+    fn interpret_evaluation_for_player(
+        &self,
+        evaluation: &Self::StateEvaluation,
+        player: &mcts::Player<ChessPuctMCTS>,
+    ) -> f64 {
+        self.interpret(evaluation, player)
+    }
+}
+
+impl GameEvaluator<ChessGame> for ChessEvaluator {
+    type Evaluation = ChessEvaluation;
+
+    fn evaluate(&self, node: &GameWrapper, _depth: usize) -> Self::Evaluation {
+        self.evaluate_state(node)
+    }
+
+    fn interpret_for_player(&self, evaluation: &Self::Evaluation, player: Color) -> f64 {
+        self.interpret(evaluation, &player)
     }
 }
 
@@ -113,8 +454,10 @@ fn score_piece(turn: Color, board: &Board, piece: Piece, score: f64) -> f64 {
 
 // https://www.chessprogramming.org/Simplified_Evaluation_Function
 mod simplified_evaluation_function {
-    use chess::{Board, Color, Piece};
-    use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+    use chess::{Board, Color, Piece, Square};
+    use rayon::prelude::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
+
+    use super::params::{EvalParams, PieceSquareTable};
 
     const CENTIPAWN_PAWN: f32 = 100f32;
     const CENTIPAWN_KNIGHT: f32 = 320f32;
@@ -224,71 +567,139 @@ mod simplified_evaluation_function {
         ],
     ];
 
-    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-    pub enum GameTime {
-        Start,
-        Middle,
-        End,
+    const WHITE_PAWN_END_MATRIX: [[f32; 8]; 8] = [
+        [0f32, 0f32, 0f32, 0f32, 0f32, 0f32, 0f32, 0f32],
+        [80f32, 80f32, 80f32, 80f32, 80f32, 80f32, 80f32, 80f32],
+        [50f32, 50f32, 50f32, 50f32, 50f32, 50f32, 50f32, 50f32],
+        [30f32, 30f32, 30f32, 30f32, 30f32, 30f32, 30f32, 30f32],
+        [15f32, 15f32, 15f32, 15f32, 15f32, 15f32, 15f32, 15f32],
+        [5f32, 5f32, 5f32, 5f32, 5f32, 5f32, 5f32, 5f32],
+        [0f32, 0f32, 0f32, 0f32, 0f32, 0f32, 0f32, 0f32],
+        [0f32, 0f32, 0f32, 0f32, 0f32, 0f32, 0f32, 0f32],
+    ];
+
+    const WHITE_KNIGHT_END_MATRIX: [[f32; 8]; 8] = [
+        [
+            -50f32, -40f32, -30f32, -30f32, -30f32, -30f32, -40f32, -50f32,
+        ],
+        [-40f32, -20f32, -10f32, -5f32, -5f32, -10f32, -20f32, -40f32],
+        [-30f32, -10f32, 5f32, 10f32, 10f32, 5f32, -10f32, -30f32],
+        [-30f32, -5f32, 10f32, 15f32, 15f32, 10f32, -5f32, -30f32],
+        [-30f32, -5f32, 10f32, 15f32, 15f32, 10f32, -5f32, -30f32],
+        [-30f32, -10f32, 5f32, 10f32, 10f32, 5f32, -10f32, -30f32],
+        [-40f32, -20f32, -10f32, -5f32, -5f32, -10f32, -20f32, -40f32],
+        [
+            -50f32, -40f32, -30f32, -30f32, -30f32, -30f32, -40f32, -50f32,
+        ],
+    ];
+
+    const WHITE_BISHOP_END_MATRIX: [[f32; 8]; 8] = [
+        [
+            -20f32, -10f32, -10f32, -10f32, -10f32, -10f32, -10f32, -20f32,
+        ],
+        [-10f32, 0f32, 0f32, 0f32, 0f32, 0f32, 0f32, -10f32],
+        [-10f32, 0f32, 5f32, 5f32, 5f32, 5f32, 0f32, -10f32],
+        [-10f32, 0f32, 5f32, 10f32, 10f32, 5f32, 0f32, -10f32],
+        [-10f32, 0f32, 5f32, 10f32, 10f32, 5f32, 0f32, -10f32],
+        [-10f32, 0f32, 5f32, 5f32, 5f32, 5f32, 0f32, -10f32],
+        [-10f32, 0f32, 0f32, 0f32, 0f32, 0f32, 0f32, -10f32],
+        [
+            -20f32, -10f32, -10f32, -10f32, -10f32, -10f32, -10f32, -20f32,
+        ],
+    ];
+
+    const WHITE_ROOK_END_MATRIX: [[f32; 8]; 8] = [
+        [0f32, 0f32, 0f32, 0f32, 0f32, 0f32, 0f32, 0f32],
+        [10f32, 10f32, 10f32, 10f32, 10f32, 10f32, 10f32, 10f32],
+        [0f32, 0f32, 0f32, 0f32, 0f32, 0f32, 0f32, 0f32],
+        [0f32, 0f32, 0f32, 0f32, 0f32, 0f32, 0f32, 0f32],
+        [0f32, 0f32, 0f32, 0f32, 0f32, 0f32, 0f32, 0f32],
+        [0f32, 0f32, 0f32, 0f32, 0f32, 0f32, 0f32, 0f32],
+        [0f32, 0f32, 0f32, 0f32, 0f32, 0f32, 0f32, 0f32],
+        [0f32, 0f32, 0f32, 0f32, 0f32, 0f32, 0f32, 0f32],
+    ];
+
+    const WHITE_QUEEN_END_MATRIX: [[f32; 8]; 8] = [
+        [-20f32, -10f32, -10f32, -5f32, -5f32, -10f32, -10f32, -20f32],
+        [-10f32, 0f32, 5f32, 5f32, 5f32, 5f32, 0f32, -10f32],
+        [-10f32, 5f32, 10f32, 10f32, 10f32, 10f32, 5f32, -10f32],
+        [-5f32, 5f32, 10f32, 15f32, 15f32, 10f32, 5f32, -5f32],
+        [-5f32, 5f32, 10f32, 15f32, 15f32, 10f32, 5f32, -5f32],
+        [-10f32, 5f32, 10f32, 10f32, 10f32, 10f32, 5f32, -10f32],
+        [-10f32, 0f32, 5f32, 5f32, 5f32, 5f32, 0f32, -10f32],
+        [-20f32, -10f32, -10f32, -5f32, -5f32, -10f32, -10f32, -20f32],
+    ];
+
+    pub(super) const PIECE_VALUES: [f32; 5] = [
+        CENTIPAWN_PAWN,
+        CENTIPAWN_KNIGHT,
+        CENTIPAWN_BISHOP,
+        CENTIPAWN_ROOK,
+        CENTIPAWN_QUEEN,
+    ];
+
+    pub(super) const MID_TABLES: [PieceSquareTable; 6] = [
+        WHITE_PAWN_MATRIX,
+        WHITE_KNIGHT_MATRIX,
+        WHITE_BISHOP_MATRIX,
+        WHITE_ROOK_MATRIX,
+        WHITE_QUEEN_MATRIX,
+        WHITE_KING_MID_MATRIX,
+    ];
+
+    pub(super) const END_TABLES: [PieceSquareTable; 6] = [
+        WHITE_PAWN_END_MATRIX,
+        WHITE_KNIGHT_END_MATRIX,
+        WHITE_BISHOP_END_MATRIX,
+        WHITE_ROOK_END_MATRIX,
+        WHITE_QUEEN_END_MATRIX,
+        WHITE_KING_END_MATRIX,
+    ];
+
+    /// Phase weight of each non-pawn piece; the starting position adds up to `MAX_PHASE`.
+    const KNIGHT_PHASE: u32 = 1;
+    const BISHOP_PHASE: u32 = 1;
+    const ROOK_PHASE: u32 = 2;
+    const QUEEN_PHASE: u32 = 4;
+    const MAX_PHASE: u32 = 4 * KNIGHT_PHASE + 4 * BISHOP_PHASE + 4 * ROOK_PHASE + 2 * QUEEN_PHASE;
+
+    /// Game phase from the remaining non-pawn material: 1.0 in the middlegame, falling
+    /// to 0.0 when only kings and pawns are left. Promotions are capped at `MAX_PHASE`.
+    pub fn game_phase(board: &Board) -> f32 {
+        let phase = board.pieces(Piece::Knight).popcnt() * KNIGHT_PHASE
+            + board.pieces(Piece::Bishop).popcnt() * BISHOP_PHASE
+            + board.pieces(Piece::Rook).popcnt() * ROOK_PHASE
+            + board.pieces(Piece::Queen).popcnt() * QUEEN_PHASE;
+
+        phase.min(MAX_PHASE) as f32 / MAX_PHASE as f32
     }
 
-    pub fn board_value(turn: Color, board: &Board, game: GameTime) -> f32 {
-        let king_matrix = match game {
-            GameTime::Start => WHITE_KING_MID_MATRIX,
-            GameTime::Middle => WHITE_KING_MID_MATRIX,
-            GameTime::End => WHITE_KING_END_MATRIX,
-        };
+    /// Material plus piece-square tables, interpolated between the middlegame and
+    /// endgame tables by [`game_phase`].
+    pub fn board_value(turn: Color, board: &Board, params: &EvalParams) -> f32 {
+        let phase = game_phase(board);
+
         [
-            (Piece::Pawn, Color::White, CENTIPAWN_PAWN, WHITE_PAWN_MATRIX),
-            (
-                Piece::Knight,
-                Color::White,
-                CENTIPAWN_KNIGHT,
-                WHITE_KNIGHT_MATRIX,
-            ),
-            (
-                Piece::Bishop,
-                Color::White,
-                CENTIPAWN_BISHOP,
-                WHITE_BISHOP_MATRIX,
-            ),
-            (Piece::Rook, Color::White, CENTIPAWN_ROOK, WHITE_ROOK_MATRIX),
-            (
-                Piece::Queen,
-                Color::White,
-                CENTIPAWN_QUEEN,
-                WHITE_QUEEN_MATRIX,
-            ),
-            (
-                Piece::King,
-                Color::White,
-                CENTIPAWN_KING,
-                king_matrix.clone(),
-            ),
-            (Piece::Pawn, Color::Black, CENTIPAWN_PAWN, WHITE_PAWN_MATRIX),
-            (
-                Piece::Knight,
-                Color::Black,
-                CENTIPAWN_KNIGHT,
-                WHITE_KNIGHT_MATRIX,
-            ),
-            (
-                Piece::Bishop,
-                Color::Black,
-                CENTIPAWN_BISHOP,
-                WHITE_BISHOP_MATRIX,
-            ),
-            (Piece::Rook, Color::Black, CENTIPAWN_ROOK, WHITE_ROOK_MATRIX),
-            (
-                Piece::Queen,
-                Color::Black,
-                CENTIPAWN_QUEEN,
-                WHITE_QUEEN_MATRIX,
-            ),
-            (Piece::King, Color::Black, CENTIPAWN_KING, king_matrix),
+            Piece::Pawn,
+            Piece::Knight,
+            Piece::Bishop,
+            Piece::Rook,
+            Piece::Queen,
+            Piece::King,
         ]
         .into_par_iter()
-        .map(|(piece, color, piece_value, piece_matrix)| {
-            let value = calculate_piece(color, board, piece, piece_value, piece_matrix);
+        .enumerate()
+        .flat_map_iter(|entry| [(Color::White, entry), (Color::Black, entry)])
+        .map(|(color, (index, piece))| {
+            let piece_value = params
+                .piece_values
+                .get(index)
+                .copied()
+                .unwrap_or(CENTIPAWN_KING);
+            let (mid_table, end_table) = (&params.mid_tables[index], &params.end_tables[index]);
+            let value = calculate_piece(color, board, piece, piece_value, |rank, file| {
+                phase * mid_table[rank][file] + (1f32 - phase) * end_table[rank][file]
+            });
 
             match color == turn {
                 true => value,
@@ -298,12 +709,31 @@ mod simplified_evaluation_function {
         .sum::<f32>()
     }
 
+    /// Piece-square value of `piece` of `color` on `square`, tapered by `phase`.
+    pub fn square_value(
+        color: Color,
+        piece: Piece,
+        square: Square,
+        phase: f32,
+        params: &EvalParams,
+    ) -> f32 {
+        let rank = match color {
+            Color::Black => square.get_rank() as usize,
+            Color::White => 7 - square.get_rank() as usize,
+        };
+        let file = square.get_file() as usize;
+        let index = piece.to_index();
+
+        phase * params.mid_tables[index][rank][file]
+            + (1f32 - phase) * params.end_tables[index][rank][file]
+    }
+
     fn calculate_piece(
         turn: Color,
         board: &Board,
         piece: Piece,
         piece_value: f32,
-        piece_matrix: [[f32; 8]; 8],
+        square_value: impl Fn(usize, usize) -> f32,
     ) -> f32 {
         let pieces = board.pieces(piece) & board.color_combined(turn);
 
@@ -314,8 +744,8 @@ mod simplified_evaluation_function {
                 let file = square.get_file() as usize;
 
                 match turn {
-                    Color::Black => piece_matrix[rank][file],
-                    Color::White => piece_matrix[7 - rank][file],
+                    Color::Black => square_value(rank, file),
+                    Color::White => square_value(7 - rank, file),
                 }
             })
             .sum();
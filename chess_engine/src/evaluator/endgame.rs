@@ -0,0 +1,111 @@
+//! Mop-up evaluation of basic won endings. Against a lone king the material and
+//! piece-square terms barely change from one move to the next, so the winning side is
+//! rewarded for driving the enemy king to the edge, or into a corner it can be mated in
+//! with bishop and knight, and for bringing its own king closer.
+
+use chess::{BitBoard, Board, Color, Piece, Square, ALL_COLORS, EMPTY};
+
+use super::EvalWeights;
+
+const DARK_SQUARES: u64 = 0xaa55_aa55_aa55_aa55;
+
+/// Where the losing king has to be driven to be mated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatingNet {
+    Edge,
+    /// The corners of the bishop's colour, for bishop and knight.
+    DarkCorner,
+    LightCorner,
+}
+
+/// Mop-up score of `board` in centipawns for `turn`, zero unless one side has a basic
+/// won ending.
+pub fn evaluate(turn: Color, board: &Board, weights: &EvalWeights) -> f32 {
+    ALL_COLORS
+        .iter()
+        .filter_map(|&strong| {
+            let net = mating_net(strong, board)?;
+            let value = mop_up(strong, net, board, weights);
+            Some(if strong == turn { value } else { -value })
+        })
+        .sum()
+}
+
+/// The mating net `strong` plays for when it has a basic won ending: mating material
+/// against a bare king, or a queen against a lone rook or minor piece.
+pub fn mating_net(strong: Color, board: &Board) -> Option<MatingNet> {
+    let own = *board.color_combined(strong);
+    let enemy = *board.color_combined(!strong);
+    let count = |piece: Piece, side: BitBoard| (board.pieces(piece) & side).popcnt();
+
+    // The weak side keeps its king and at most one piece, and no pawns.
+    let enemy_pieces = (enemy & !board.pieces(Piece::King)).popcnt();
+    if enemy_pieces > 1 || count(Piece::Pawn, enemy) > 0 || count(Piece::Queen, enemy) > 0 {
+        return None;
+    }
+
+    if count(Piece::Queen, own) > 0 {
+        return Some(MatingNet::Edge);
+    }
+    if enemy_pieces > 0 {
+        return None;
+    }
+
+    let bishops = board.pieces(Piece::Bishop) & own;
+    let dark_bishops = (bishops.0 & DARK_SQUARES).count_ones();
+    let light_bishops = bishops.popcnt() - dark_bishops;
+
+    if count(Piece::Rook, own) > 0 || (dark_bishops > 0 && light_bishops > 0) {
+        Some(MatingNet::Edge)
+    } else if count(Piece::Knight, own) > 0 && bishops != EMPTY {
+        match dark_bishops > 0 {
+            true => Some(MatingNet::DarkCorner),
+            false => Some(MatingNet::LightCorner),
+        }
+    } else {
+        None
+    }
+}
+
+/// Bonus for `strong` for how far the enemy king has been pushed towards `net` and how
+/// close the kings stand.
+fn mop_up(strong: Color, net: MatingNet, board: &Board, weights: &EvalWeights) -> f32 {
+    let king = board.king_square(strong);
+    let weak_king = board.king_square(!strong);
+
+    let pushed = match net {
+        MatingNet::Edge => center_distance(weak_king),
+        MatingNet::DarkCorner => corner_closeness(weak_king, [Square::A1, Square::H8]),
+        MatingNet::LightCorner => corner_closeness(weak_king, [Square::A8, Square::H1]),
+    };
+    let closeness = 14 - distance(king, weak_king);
+
+    pushed as f32 * weights.mop_up_edge + closeness as f32 * weights.mop_up_kings
+}
+
+/// Manhattan distance from `square` to the four centre squares, 0 to 6.
+fn center_distance(square: Square) -> u32 {
+    let (file, rank) = coordinates(square);
+    (3 - file).max(file - 4) as u32 + (3 - rank).max(rank - 4) as u32
+}
+
+/// How close `square` is to the nearer of `corners`, 0 to 7.
+fn corner_closeness(square: Square, corners: [Square; 2]) -> u32 {
+    let nearest = corners
+        .map(|corner| distance(square, corner))
+        .into_iter()
+        .min();
+    (14 - nearest.unwrap()) / 2
+}
+
+fn distance(a: Square, b: Square) -> u32 {
+    let (a, b) = (coordinates(a), coordinates(b));
+    (a.0 - b.0).unsigned_abs() + (a.1 - b.1).unsigned_abs()
+}
+
+fn coordinates(square: Square) -> (i32, i32) {
+    (
+        square.get_file().to_index() as i32,
+        square.get_rank().to_index() as i32,
+    )
+}
@@ -0,0 +1,66 @@
+//! King safety: the pawn shield in front of the king, open files next to it and the
+//! enemy pieces attacking the squares around it.
+
+use chess::{
+    get_adjacent_files, get_file, get_king_moves, get_rank, BitBoard, Board, Color, File, Piece,
+    EMPTY,
+};
+
+use super::{mobility::attacks, EvalWeights};
+
+/// King-safety score of `board` in centipawns for `turn`, faded out towards the
+/// endgame by `phase`.
+pub fn evaluate(turn: Color, board: &Board, weights: &EvalWeights, phase: f32) -> f32 {
+    (side_safety(turn, board, weights) - side_safety(!turn, board, weights)) * phase
+}
+
+fn side_safety(color: Color, board: &Board, weights: &EvalWeights) -> f32 {
+    let king = board.king_square(color);
+    let pawns = *board.pieces(Piece::Pawn);
+    let own_pawns = pawns & board.color_combined(color);
+    let king_files = get_file(king.get_file()) | get_adjacent_files(king.get_file());
+
+    // Own pawns on the two ranks in front of the king.
+    let mut shield_ranks = EMPTY;
+    let mut square = king;
+    for _ in 0..2 {
+        match square.forward(color) {
+            Some(next) => {
+                shield_ranks |= get_rank(next.get_rank());
+                square = next;
+            }
+            None => break,
+        }
+    }
+    let shield = (own_pawns & king_files & shield_ranks).popcnt() as f32 * weights.pawn_shield;
+
+    let file_index = king.get_file().to_index();
+    let files = (file_index.saturating_sub(1)..=(file_index + 1).min(7))
+        .map(|file| {
+            let file = get_file(File::from_index(file));
+            if pawns & file == EMPTY {
+                weights.open_file
+            } else if own_pawns & file == EMPTY {
+                weights.semi_open_file
+            } else {
+                0f32
+            }
+        })
+        .sum::<f32>();
+
+    let zone = get_king_moves(king) | BitBoard::from_square(king);
+    let enemy = board.color_combined(!color);
+    let (attackers, zone_attacks) = [Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen]
+        .into_iter()
+        .flat_map(|piece| (board.pieces(piece) & enemy).map(move |square| (piece, square)))
+        .map(|(piece, square)| (attacks(piece, square, !color, *board.combined()) & zone).popcnt())
+        .filter(|&hits| hits > 0)
+        .fold((0u32, 0u32), |(attackers, zone_attacks), hits| {
+            (attackers + 1, zone_attacks + hits)
+        });
+
+    shield
+        + files
+        + attackers as f32 * weights.king_attackers
+        + zone_attacks as f32 * weights.king_zone_attacks
+}
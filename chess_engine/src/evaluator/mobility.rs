@@ -0,0 +1,54 @@
+//! Mobility: the squares each piece attacks that are neither occupied by its own side
+//! nor attacked by enemy pawns.
+
+use chess::{
+    get_bishop_moves, get_king_moves, get_knight_moves, get_pawn_attacks, get_rook_moves, BitBoard,
+    Board, Color, Piece, Square, EMPTY,
+};
+
+use super::EvalWeights;
+
+/// Mobility score of `board` in centipawns for `turn`.
+pub fn evaluate(turn: Color, board: &Board, weights: &EvalWeights) -> f32 {
+    side_mobility(turn, board, weights) - side_mobility(!turn, board, weights)
+}
+
+fn side_mobility(color: Color, board: &Board, weights: &EvalWeights) -> f32 {
+    let own = board.color_combined(color);
+    let safe = !own & !pawn_attacks(!color, board);
+
+    [
+        (Piece::Knight, weights.knight_mobility),
+        (Piece::Bishop, weights.bishop_mobility),
+        (Piece::Rook, weights.rook_mobility),
+        (Piece::Queen, weights.queen_mobility),
+    ]
+    .into_iter()
+    .map(|(piece, weight)| {
+        (board.pieces(piece) & own)
+            .map(|square| {
+                let moves = attacks(piece, square, color, *board.combined()) & safe;
+                moves.popcnt() as f32 * weight
+            })
+            .sum::<f32>()
+    })
+    .sum()
+}
+
+/// Squares attacked by `piece` of `color` on `square`, with `occupied` blocking sliders.
+pub(super) fn attacks(piece: Piece, square: Square, color: Color, occupied: BitBoard) -> BitBoard {
+    match piece {
+        Piece::Pawn => get_pawn_attacks(square, color, !EMPTY),
+        Piece::Knight => get_knight_moves(square),
+        Piece::Bishop => get_bishop_moves(square, occupied),
+        Piece::Rook => get_rook_moves(square, occupied),
+        Piece::Queen => get_bishop_moves(square, occupied) | get_rook_moves(square, occupied),
+        Piece::King => get_king_moves(square),
+    }
+}
+
+fn pawn_attacks(color: Color, board: &Board) -> BitBoard {
+    (board.pieces(Piece::Pawn) & board.color_combined(color)).fold(EMPTY, |attacked, square| {
+        attacked | attacks(Piece::Pawn, square, color, EMPTY)
+    })
+}
@@ -0,0 +1,141 @@
+//! All tunable evaluation parameters in one place, with a flat vector view for the
+//! tuner. Weight sets are stored as TOML, or as JSON when the file ends in `.json`;
+//! parameters missing from a file keep their built-in defaults.
+
+use std::{fs, io, path::Path, slice};
+
+use serde::{Deserialize, Serialize};
+
+use super::{pawns::PawnWeights, simplified_evaluation_function, EvalWeights};
+
+/// Piece-square table from white's point of view, rank 8 first.
+pub type PieceSquareTable = [[f32; 8]; 8];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EvalParams {
+    /// Pawn, knight, bishop, rook and queen values.
+    pub piece_values: [f32; 5],
+    /// Middlegame tables for pawn, knight, bishop, rook, queen and king.
+    pub mid_tables: [PieceSquareTable; 6],
+    /// Endgame tables, in the same order.
+    pub end_tables: [PieceSquareTable; 6],
+    pub pawns: PawnWeights,
+    pub weights: EvalWeights,
+}
+
+impl Default for EvalParams {
+    fn default() -> Self {
+        Self {
+            piece_values: simplified_evaluation_function::PIECE_VALUES,
+            mid_tables: simplified_evaluation_function::MID_TABLES,
+            end_tables: simplified_evaluation_function::END_TABLES,
+            pawns: PawnWeights::default(),
+            weights: EvalWeights::default(),
+        }
+    }
+}
+
+impl EvalParams {
+    /// Calls `f` with every parameter group, always in the same order.
+    pub fn visit_mut(&mut self, mut f: impl FnMut(&mut [f32])) {
+        f(&mut self.piece_values);
+        for table in self.mid_tables.iter_mut().chain(&mut self.end_tables) {
+            f(table.as_flattened_mut());
+        }
+
+        let pawns = &mut self.pawns;
+        f(slice::from_mut(&mut pawns.doubled));
+        f(slice::from_mut(&mut pawns.isolated));
+        f(slice::from_mut(&mut pawns.backward));
+        f(slice::from_mut(&mut pawns.island));
+        f(&mut pawns.passed);
+        f(slice::from_mut(&mut pawns.blocked_passed));
+
+        let weights = &mut self.weights;
+        f(slice::from_mut(&mut weights.knight_mobility));
+        f(slice::from_mut(&mut weights.bishop_mobility));
+        f(slice::from_mut(&mut weights.rook_mobility));
+        f(slice::from_mut(&mut weights.queen_mobility));
+        f(slice::from_mut(&mut weights.pawn_shield));
+        f(slice::from_mut(&mut weights.open_file));
+        f(slice::from_mut(&mut weights.semi_open_file));
+        f(slice::from_mut(&mut weights.king_attackers));
+        f(slice::from_mut(&mut weights.king_zone_attacks));
+        f(slice::from_mut(&mut weights.mop_up_edge));
+        f(slice::from_mut(&mut weights.mop_up_kings));
+    }
+
+    pub fn to_vector(&self) -> Vec<f32> {
+        let mut vector = Vec::new();
+        self.clone()
+            .visit_mut(|values| vector.extend_from_slice(values));
+        vector
+    }
+
+    /// Tuning step of every entry of [`EvalParams::to_vector`] for a step of
+    /// `centipawns`. The share of the passed pawn bonus kept when blocked moves by a
+    /// hundredth of it instead.
+    pub fn steps(centipawns: f32) -> Vec<f32> {
+        let mut steps = EvalParams::default();
+        steps.visit_mut(|values| values.fill(centipawns));
+        steps.pawns.blocked_passed = centipawns / 100.0;
+        steps.to_vector()
+    }
+
+    /// Inverse of [`EvalParams::to_vector`].
+    pub fn from_vector(vector: &[f32]) -> Self {
+        let mut params = EvalParams::default();
+        let mut rest = vector;
+        params.visit_mut(|values| {
+            let (head, tail) = rest.split_at(values.len());
+            values.copy_from_slice(head);
+            rest = tail;
+        });
+        params
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(text)
+    }
+
+    pub fn to_toml(&self) -> Result<String, toml::ser::Error> {
+        toml::to_string(self)
+    }
+
+    pub fn from_json(text: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(text)
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Reads a weight set, as TOML or as JSON depending on the extension of `path`.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let text = fs::read_to_string(&path)?;
+
+        match is_json(path.as_ref()) {
+            true => Self::from_json(&text).map_err(invalid_data),
+            false => Self::from_toml(&text).map_err(invalid_data),
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let text = match is_json(path.as_ref()) {
+            true => self.to_json().map_err(invalid_data)?,
+            false => self.to_toml().map_err(invalid_data)?,
+        };
+
+        fs::write(path, text)
+    }
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == "json")
+}
+
+fn invalid_data(error: impl std::fmt::Display) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error.to_string())
+}
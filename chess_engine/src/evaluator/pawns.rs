@@ -0,0 +1,201 @@
+//! Pawn-structure terms. The structure only depends on the pawns, so it is cached in a
+//! [`PawnTable`] keyed on [`Board::get_pawn_hash`]; whether a passed pawn is blocked
+//! also depends on the other pieces and is scored on every call.
+
+use std::sync::Mutex;
+
+use chess::{Board, Color, Piece};
+use serde::{Deserialize, Serialize};
+
+const FILE_A: u64 = 0x0101_0101_0101_0101;
+const FILE_H: u64 = FILE_A << 7;
+
+/// Weights of the pawn-structure terms, in centipawns.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PawnWeights {
+    pub doubled: f32,
+    pub isolated: f32,
+    pub backward: f32,
+    /// Applied to every pawn island after the first.
+    pub island: f32,
+    /// Passed pawn bonus by rank, seen from the pawn's own side.
+    pub passed: [f32; 8],
+    /// Share of the passed pawn bonus kept when the square in front of it is occupied.
+    pub blocked_passed: f32,
+}
+
+impl Default for PawnWeights {
+    fn default() -> Self {
+        Self {
+            doubled: -12f32,
+            isolated: -12f32,
+            backward: -8f32,
+            island: -6f32,
+            passed: [0f32, 5f32, 10f32, 20f32, 35f32, 60f32, 100f32, 0f32],
+            blocked_passed: 0.5f32,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct PawnEntry {
+    key: Option<u64>,
+    /// Doubled, isolated, backward and island terms, from white's point of view.
+    structure: f32,
+    /// Passed pawns per colour, black's mirrored so both advance towards rank 8.
+    passed: [u64; 2],
+}
+
+/// Fixed-size cache of pawn-structure evaluations with `weights`, shared by all search
+/// threads.
+pub struct PawnTable {
+    weights: PawnWeights,
+    entries: Vec<Mutex<PawnEntry>>,
+}
+
+impl PawnTable {
+    pub fn new(entries: usize, weights: PawnWeights) -> Self {
+        Self {
+            weights,
+            entries: (0..entries.max(1))
+                .map(|_| Mutex::new(PawnEntry::default()))
+                .collect(),
+        }
+    }
+
+    /// Forgets every cached entry and evaluates with `weights` from now on.
+    pub fn reset(&mut self, weights: PawnWeights) {
+        self.weights = weights;
+        for entry in &mut self.entries {
+            *entry.get_mut().unwrap() = PawnEntry::default();
+        }
+    }
+
+    fn probe(&self, board: &Board) -> PawnEntry {
+        let key = board.get_pawn_hash();
+        let slot = &self.entries[(key % self.entries.len() as u64) as usize];
+
+        let cached = *slot.lock().unwrap();
+        if cached.key == Some(key) {
+            return cached;
+        }
+
+        let pawns = board.pieces(Piece::Pawn);
+        let entry = pawn_structure(
+            &self.weights,
+            key,
+            (pawns & board.color_combined(Color::White)).0,
+            (pawns & board.color_combined(Color::Black)).0,
+        );
+        *slot.lock().unwrap() = entry;
+        entry
+    }
+}
+
+impl Default for PawnTable {
+    fn default() -> Self {
+        PawnTable::new(1 << 14, PawnWeights::default())
+    }
+}
+
+/// Pawn-structure score of `board` in centipawns for `turn`.
+pub fn evaluate(turn: Color, board: &Board, table: &PawnTable) -> f32 {
+    let entry = table.probe(board);
+    let occupied = board.combined().0;
+    let weights = &table.weights;
+
+    let white = entry.structure + passed_bonus(weights, entry.passed[0], occupied);
+    let black = passed_bonus(weights, entry.passed[1], occupied.swap_bytes());
+
+    match turn {
+        Color::White => white - black,
+        Color::Black => black - white,
+    }
+}
+
+fn pawn_structure(weights: &PawnWeights, key: u64, white: u64, black: u64) -> PawnEntry {
+    let (white_score, white_passed) = side_structure(weights, white, black);
+    let (black_score, black_passed) =
+        side_structure(weights, black.swap_bytes(), white.swap_bytes());
+
+    PawnEntry {
+        key: Some(key),
+        structure: white_score - black_score,
+        passed: [white_passed, black_passed],
+    }
+}
+
+/// Scores the pawns of the side moving towards rank 8; returns the score and the
+/// passed pawns.
+fn side_structure(weights: &PawnWeights, own: u64, enemy: u64) -> (f32, u64) {
+    let enemy_attacks = ((enemy >> 7) & !FILE_A) | ((enemy >> 9) & !FILE_H);
+    let mut score = 0f32;
+    let mut passed = 0u64;
+
+    let files = (0..8).filter(|&file| own & (FILE_A << file) != 0);
+    let occupied_files = files.fold(0u64, |mask, file| mask | 1 << file);
+    let islands = (occupied_files & !(occupied_files << 1)).count_ones();
+    score += islands.saturating_sub(1) as f32 * weights.island;
+
+    for file in 0..8 {
+        let count = (own & (FILE_A << file)).count_ones();
+        score += count.saturating_sub(1) as f32 * weights.doubled;
+    }
+
+    let mut pawns = own;
+    while pawns != 0 {
+        let square = pawns.trailing_zeros();
+        pawns &= pawns - 1;
+
+        let bit = 1u64 << square;
+        let stop = bit << 8;
+        let adjacent = adjacent_files(square % 8);
+        let ahead = north_fill(stop);
+        let behind_or_level = !u64::MAX.checked_shl(square / 8 * 8 + 8).unwrap_or(0);
+
+        if own & adjacent == 0 {
+            score += weights.isolated;
+        } else if own & adjacent & behind_or_level == 0 && enemy_attacks & stop != 0 {
+            // No neighbour can come up to support it and its stop square is attacked.
+            score += weights.backward;
+        }
+
+        let front_span = ahead | ((ahead << 1) & !FILE_A) | ((ahead >> 1) & !FILE_H);
+        if enemy & front_span == 0 {
+            passed |= bit;
+        }
+    }
+
+    (score, passed)
+}
+
+fn passed_bonus(weights: &PawnWeights, passed: u64, occupied: u64) -> f32 {
+    let mut bonus = 0f32;
+    let mut pawns = passed;
+
+    while pawns != 0 {
+        let square = pawns.trailing_zeros();
+        pawns &= pawns - 1;
+
+        let value = weights.passed[(square / 8) as usize];
+        bonus += match occupied & (1u64 << square << 8) != 0 {
+            true => value * weights.blocked_passed,
+            false => value,
+        };
+    }
+
+    bonus
+}
+
+fn adjacent_files(file: u32) -> u64 {
+    let file = FILE_A << file;
+    ((file << 1) & !FILE_A) | ((file >> 1) & !FILE_H)
+}
+
+fn north_fill(mut bits: u64) -> u64 {
+    bits |= bits << 8;
+    bits |= bits << 16;
+    bits |= bits << 32;
+    bits
+}
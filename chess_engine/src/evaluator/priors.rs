@@ -0,0 +1,69 @@
+//! Move priors for PUCT. Every move gets a cheap score in centipawns: the gain in the
+//! piece-square tables, captures ordered by most valuable victim and least valuable
+//! attacker (MVV-LVA), what the exchange on the target square loses (SEE), checks and
+//! promotions. A softmax turns the scores into probabilities.
+
+use chess::{Board, ChessMove, Piece};
+
+use super::{params::EvalParams, simplified_evaluation_function};
+use crate::see::{piece_value, see};
+
+/// Score difference in centipawns that makes one move e times as likely as another.
+pub const PRIOR_TEMPERATURE: f64 = 100.0;
+const CHECK_BONUS: f64 = 50.0;
+/// Part of the attacker's value taken off a capture, so the cheapest attacker of a
+/// victim comes first.
+const ATTACKER_SHARE: f64 = 0.1;
+
+/// Score of `mov` in `board` in centipawns, for the side to move.
+pub fn move_score(board: &Board, mov: ChessMove, params: &EvalParams) -> f64 {
+    let color = board.side_to_move();
+    let phase = simplified_evaluation_function::game_phase(board);
+    let square_value = |piece, square| {
+        simplified_evaluation_function::square_value(color, piece, square, phase, params) as f64
+    };
+
+    let piece = board
+        .piece_on(mov.get_source())
+        .expect("a piece on the source square");
+    let mut score = match mov.get_promotion() {
+        Some(promotion) => {
+            square_value(promotion, mov.get_dest()) - square_value(piece, mov.get_source())
+                + (piece_value(promotion) - piece_value(Piece::Pawn)) as f64
+        }
+        None => square_value(piece, mov.get_dest()) - square_value(piece, mov.get_source()),
+    };
+
+    if let Some(victim) = board.piece_on(mov.get_dest()) {
+        score += piece_value(victim) as f64 - ATTACKER_SHARE * piece_value(piece) as f64;
+    }
+
+    score += see(board, mov).min(0) as f64;
+
+    if board.make_move_new(mov).checkers().popcnt() > 0 {
+        score += CHECK_BONUS;
+    }
+
+    score
+}
+
+/// Prior probability of each of `moves` in `board`, summing to one.
+pub fn move_priors(board: &Board, moves: &[ChessMove], params: &EvalParams) -> Vec<f64> {
+    let scores = moves
+        .iter()
+        .map(|mov| move_score(board, *mov, params) / PRIOR_TEMPERATURE)
+        .collect::<Vec<_>>();
+
+    softmax(&scores)
+}
+
+fn softmax(scores: &[f64]) -> Vec<f64> {
+    let max = scores.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let exps = scores
+        .iter()
+        .map(|score| (score - max).exp())
+        .collect::<Vec<_>>();
+    let sum = exps.iter().sum::<f64>();
+
+    exps.into_iter().map(|exp| exp / sum).collect()
+}
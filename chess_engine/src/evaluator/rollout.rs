@@ -0,0 +1,130 @@
+//! Rollouts: judging a new leaf of the MCTS tree by playing the game on from it instead
+//! of only evaluating it statically. Random rollouts pick every move uniformly, heavy
+//! rollouts favour captures that don't lose material and checks. A rollout ends with
+//! the game or after a number of plies, where the position reached is evaluated
+//! statically.
+
+use chess::{Board, ChessMove, MoveGen};
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+    see::{piece_value, see_ge},
+    state::Position,
+};
+
+/// Score in centipawns of a rollout that ends in mate. Random play mating is weak
+/// evidence, so it counts for about a rook more than a clearly won position.
+pub const ROLLOUT_WIN: f64 = 1000.0;
+/// Plies a rollout plays when none are configured.
+pub const DEFAULT_ROLLOUT_PLIES: usize = 40;
+/// Share of the rollout in a hybrid evaluation when none is configured.
+pub const DEFAULT_ROLLOUT_WEIGHT: f64 = 0.5;
+
+/// Weight of a quiet move in a heavy rollout, which captures and checks add to.
+const QUIET_WEIGHT: u32 = 1;
+const CHECK_WEIGHT: u32 = 4;
+/// Weight of a capture that doesn't lose material, plus one per pawn captured.
+const CAPTURE_WEIGHT: u32 = 4;
+
+/// How a new leaf of the MCTS tree is evaluated.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum LeafEvaluation {
+    /// Static evaluation of the leaf.
+    #[default]
+    Static,
+    /// A uniformly random rollout of at most `plies`.
+    Random { plies: usize },
+    /// A heavy rollout of at most `plies`.
+    Heavy { plies: usize },
+    /// A heavy rollout blended with the static evaluation of the leaf, the rollout
+    /// counting for `rollout_weight` between 0 and 1.
+    Hybrid { plies: usize, rollout_weight: f64 },
+}
+
+impl LeafEvaluation {
+    /// The strategy called `name` (`static`, `random`, `heavy` or `hybrid`, in any case)
+    /// with rollouts of `plies`.
+    pub fn named(name: &str, plies: usize) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "static" => Some(LeafEvaluation::Static),
+            "random" => Some(LeafEvaluation::Random { plies }),
+            "heavy" => Some(LeafEvaluation::Heavy { plies }),
+            "hybrid" => Some(LeafEvaluation::Hybrid {
+                plies,
+                rollout_weight: DEFAULT_ROLLOUT_WEIGHT,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Sets the length of the rollouts, the static evaluation has none.
+    pub fn with_plies(self, plies: usize) -> Self {
+        match self {
+            LeafEvaluation::Static => LeafEvaluation::Static,
+            LeafEvaluation::Random { .. } => LeafEvaluation::Random { plies },
+            LeafEvaluation::Heavy { .. } => LeafEvaluation::Heavy { plies },
+            LeafEvaluation::Hybrid { rollout_weight, .. } => LeafEvaluation::Hybrid {
+                plies,
+                rollout_weight,
+            },
+        }
+    }
+}
+
+/// How the moves of a rollout are picked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RolloutPolicy {
+    Random,
+    Heavy,
+}
+
+/// Plays on from `position` with `policy` until the game ends or `plies` were played and
+/// returns the position reached and the plies played.
+pub fn rollout<R: Rng>(
+    position: &Position,
+    policy: RolloutPolicy,
+    plies: usize,
+    rng: &mut R,
+) -> (Position, usize) {
+    let mut position = position.clone();
+    let mut moves = Vec::new();
+
+    for played in 0..plies {
+        if position.outcome().is_some() {
+            return (position, played);
+        }
+
+        moves.clear();
+        moves.extend(MoveGen::new_legal(position.board()));
+        let mov = match policy {
+            RolloutPolicy::Random => moves.choose(rng),
+            RolloutPolicy::Heavy => moves
+                .choose_weighted(rng, |mov| heavy_weight(position.board(), *mov))
+                .ok(),
+        };
+
+        match mov {
+            Some(&mov) => position.push(mov),
+            None => return (position, played),
+        }
+    }
+
+    (position, plies)
+}
+
+/// Weight of `mov` in a heavy rollout.
+pub fn heavy_weight(board: &Board, mov: ChessMove) -> u32 {
+    let mut weight = QUIET_WEIGHT;
+
+    if let Some(victim) = board.piece_on(mov.get_dest()) {
+        if see_ge(board, mov, 0) {
+            weight += CAPTURE_WEIGHT + (piece_value(victim) / 100) as u32;
+        }
+    }
+
+    if board.make_move_new(mov).checkers().popcnt() > 0 {
+        weight += CHECK_WEIGHT;
+    }
+
+    weight
+}
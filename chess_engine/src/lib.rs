@@ -1,33 +1,119 @@
-use chess::{ChessMove, Game, Color, MoveGen};
-use evaluator::ChessEvaluator;
-use mcts::{transposition_table::ApproxTable, tree_policy::UCTPolicy, MCTSManager, Evaluator};
-use mcts_handler::ChessMCTS;
+use std::sync::atomic::AtomicBool;
+
+use chess::Game;
+use search::{MoveSource, SearchConfig, SearchReport};
 use state::GameWrapper;
 
+pub mod book;
+pub mod epd;
 pub mod evaluator;
 pub mod mcts_handler;
+#[cfg(feature = "nnue")]
+pub mod nnue;
+pub mod perft;
+pub mod search;
+pub mod see;
+pub mod session;
 pub mod state;
+pub mod tablebase;
+pub mod time_manager;
+pub mod tree_policy;
+pub mod tree_search;
+pub mod tuner;
+pub mod uci;
+pub mod xboard;
+
+/// Playouts of a [`find_move`] search that has no limits set.
+pub const DEFAULT_PLAYOUTS: u64 = 100_000;
 
-pub fn find_move(game: &Game, playouts: u32, cores: usize, color: Color) -> ChessMove {
-    
-    let mut manager = MCTSManager::new(
-        GameWrapper(game.clone()),
-        ChessMCTS,
-        ChessEvaluator(color),
-        UCTPolicy::new(10.5),
-        ApproxTable::new(1024)
-    );
+/// Exploration constant of UCT and RAVE, for rewards that are win probabilities.
+pub const UCT_EXPLORATION: f64 = 0.5;
+/// Exploration constant of PUCT, which the move priors share out between the moves.
+pub const PUCT_EXPLORATION: f64 = 1.5;
+
+/// Tree policy used to select children during the search.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SearchPolicy {
+    Uct {
+        exploration: f64,
+    },
+    Rave {
+        exploration: f64,
+        equivalence: f64,
+    },
+    /// PUCT with move priors from [`evaluator::priors`].
+    Puct {
+        exploration: f64,
+    },
+}
 
-    manager.perf_test_to_stderr(cores);
+impl SearchPolicy {
+    pub fn with_exploration(self, exploration: f64) -> Self {
+        match self {
+            SearchPolicy::Uct { .. } => SearchPolicy::Uct { exploration },
+            SearchPolicy::Rave { equivalence, .. } => SearchPolicy::Rave {
+                exploration,
+                equivalence,
+            },
+            SearchPolicy::Puct { .. } => SearchPolicy::Puct { exploration },
+        }
+    }
+}
+
+impl Default for SearchPolicy {
+    fn default() -> Self {
+        SearchPolicy::Uct {
+            exploration: UCT_EXPLORATION,
+        }
+    }
+}
 
-    let moves = MoveGen::new_legal(&game.current_position()).collect::<Vec<_>>();
-    let e = ChessEvaluator(color).evaluate_new_state(&GameWrapper(game.clone()), &moves, None);    
+/// Search algorithm used by [`find_move`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SearchAlgorithm {
+    Mcts(SearchPolicy),
+    /// Full-width minimax from `search_tree`, `depth` plies deep.
+    MiniMax {
+        depth: usize,
+    },
+}
+
+impl Default for SearchAlgorithm {
+    fn default() -> Self {
+        SearchAlgorithm::Mcts(SearchPolicy::default())
+    }
+}
+
+/// Finds a move for the side to move in `game` as configured by `config`. When the book
+/// has a move for the position it is played without searching, and so is the
+/// DTZ-optimal move when the position is in the tablebase. Without any limit the search
+/// stops after [`DEFAULT_PLAYOUTS`] playouts.
+pub fn find_move(game: &Game, config: &SearchConfig) -> SearchReport {
+    if let Some(report) = lookup_move(game, config) {
+        return report;
+    }
+
+    match config.limits.is_unlimited() {
+        true => {
+            let config = config.clone().with_playouts(DEFAULT_PLAYOUTS);
+            search::search(game, &config, &AtomicBool::new(false), |_| {})
+        }
+        false => search::search(game, config, &AtomicBool::new(false), |_| {}),
+    }
+}
 
-    manager.playout_n_parallel(playouts, cores);
-    // manager.playout_n_parallel(1, 16);
+/// The book move or else the DTZ-optimal tablebase move for `game`, if there is one.
+pub(crate) fn lookup_move(game: &Game, config: &SearchConfig) -> Option<SearchReport> {
+    let board = game.current_position();
 
-    println!("current evaluation: {:?}", e.1);
-    manager.tree().display_moves();
+    if let Some(book) = &config.book {
+        if let Some((mov, _)) = book.probe(&board) {
+            return Some(SearchReport::lookup(mov, MoveSource::Book));
+        }
+    }
 
-    manager.best_move().unwrap()
+    let tablebase = config.tablebase.as_ref()?;
+    let root = GameWrapper::new(game.clone());
+    tablebase::best_move(tablebase.as_ref(), root.board(), root.halfmove_clock())
+        .map(|(mov, _)| SearchReport::lookup(mov, MoveSource::Tablebase))
 }
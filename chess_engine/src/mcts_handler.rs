@@ -1,8 +1,16 @@
-use chess::Game;
-use mcts::{MCTS, tree_policy::UCTPolicy, transposition_table::ApproxTable};
+//! MCTS specs for chess. Rewards are win probabilities, so the visit a thread adds on
+//! its way down already counts as a loss and the specs need no extra virtual loss.
 
-use crate::{state::GameWrapper, evaluator::ChessEvaluator};
+use chess::{ChessMove, Color};
+use mcts::{transposition_table::ApproxTable, tree_policy::UCTPolicy, SearchHandle, MCTS};
 
+#[cfg(feature = "nnue")]
+use crate::nnue::NnueEvaluator;
+use crate::{
+    evaluator::{interpret_evaluation, ChessEvaluation, ChessEvaluator, WinProbability},
+    state::GameWrapper,
+    tree_policy::{backpropagate_amaf, AmafTable, PuctPolicy, RavePolicy},
+};
 
 #[derive(Default)]
 pub struct ChessMCTS;
@@ -14,9 +22,52 @@ impl MCTS for ChessMCTS {
     type TranspositionTable = ApproxTable<Self>;
     type NodeData = ();
     type ExtraThreadData = ();
+}
+
+/// Chess search using the [`RavePolicy`]; holds the colour the evaluator scores for and
+/// its [`WinProbability`], so the AMAF statistics get the same rewards as the tree.
+pub struct ChessRaveMCTS(pub Color, pub WinProbability);
+
+impl MCTS for ChessRaveMCTS {
+    type State = GameWrapper;
+    type Eval = ChessEvaluator;
+    type TreePolicy = RavePolicy;
+    type TranspositionTable = ApproxTable<Self>;
+    type NodeData = AmafTable<ChessMove>;
+    type ExtraThreadData = ();
 
-    fn virtual_loss(&self) -> f64 {
-        crate::evaluator::SCALE
+    fn on_backpropagation(&self, evaln: &ChessEvaluation, handle: SearchHandle<Self>) {
+        backpropagate_amaf(handle, |player| {
+            interpret_evaluation(self.0, evaln, player, self.1)
+        });
     }
 }
 
+/// Chess search using the [`PuctPolicy`], with move priors from
+/// [`priors`](crate::evaluator::priors).
+#[derive(Default)]
+pub struct ChessPuctMCTS;
+
+impl MCTS for ChessPuctMCTS {
+    type State = GameWrapper;
+    type Eval = ChessEvaluator;
+    type TreePolicy = PuctPolicy;
+    type TranspositionTable = ApproxTable<Self>;
+    type NodeData = ();
+    type ExtraThreadData = ();
+}
+
+/// Chess search like [`ChessMCTS`], evaluated by an [`NnueEvaluator`].
+#[cfg(feature = "nnue")]
+#[derive(Default)]
+pub struct ChessNnueMCTS;
+
+#[cfg(feature = "nnue")]
+impl MCTS for ChessNnueMCTS {
+    type State = GameWrapper;
+    type Eval = NnueEvaluator;
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ApproxTable<Self>;
+    type NodeData = ();
+    type ExtraThreadData = ();
+}
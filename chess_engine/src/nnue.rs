@@ -0,0 +1,449 @@
+//! Efficiently updatable neural network evaluation, enabled with the `nnue` feature.
+//! [`NnueEvaluator`] searches with a network alone, while
+//! [`ChessEvaluator::with_network`](crate::evaluator::ChessEvaluator::with_network) keeps
+//! the rest of the handcrafted evaluator around it.
+//!
+//! The network uses HalfKP inputs: for each side, every non-king piece is a feature
+//! relative to that side's own king. Squares are seen from the side's point of view, so
+//! black's board is mirrored vertically. The layers are
+//!
+//! ```text
+//! 2 x (41024 -> L1) -> clipped relu -> L2 -> clipped relu -> 1
+//! ```
+//!
+//! where the side to move's half of the input comes first. A network file is little
+//! endian and laid out as:
+//!
+//! | field            | type | count               |
+//! |------------------|------|---------------------|
+//! | magic `NNUEHKP1` | u8   | 8                   |
+//! | L1, L2           | u32  | 2                   |
+//! | feature biases   | i16  | L1                  |
+//! | feature weights  | i16  | 41024 x L1          |
+//! | hidden biases    | i32  | L2                  |
+//! | hidden weights   | i8   | L2 x 2·L1           |
+//! | output bias      | i32  | 1                   |
+//! | output weights   | i8   | L2                  |
+//!
+//! Hidden sums are shifted right by [`HIDDEN_SHIFT`] before clipping and the output is
+//! divided by [`OUTPUT_SCALE`] to get centipawns.
+
+use std::{fs, io, path::Path, sync::Arc};
+
+use chess::{Board, Color, Piece, Square, ALL_COLORS};
+use mcts::Evaluator;
+use search_tree::GameEvaluator;
+
+use crate::{
+    evaluator::{interpret_evaluation, ChessEvaluation, WinProbability},
+    mcts_handler::ChessNnueMCTS,
+    state::{GameWrapper, Position},
+    tree_search::ChessNnueGame,
+};
+
+mod simd;
+
+use simd::Kernels;
+
+/// Number of HalfKP features per side: 64 king squares times 10 piece kinds on 64
+/// squares, plus one unused slot per king square.
+pub const HALFKP_FEATURES: usize = 64 * 641;
+/// Right shift applied to the hidden layer sums.
+pub const HIDDEN_SHIFT: i32 = 6;
+/// Network output units per centipawn.
+pub const OUTPUT_SCALE: i32 = 16;
+
+const MAGIC: &[u8; 8] = b"NNUEHKP1";
+const MAX_L1: usize = 4096;
+const MAX_L2: usize = 1024;
+const FEATURE_PIECES: [Piece; 5] = [
+    Piece::Pawn,
+    Piece::Knight,
+    Piece::Bishop,
+    Piece::Rook,
+    Piece::Queen,
+];
+
+/// Index of the HalfKP feature for `piece` of `color` on `square`, seen by `perspective`
+/// whose king stands on `king`.
+pub fn feature_index(
+    perspective: Color,
+    king: Square,
+    piece: Piece,
+    color: Color,
+    square: Square,
+) -> usize {
+    let orient = |square: Square| match perspective {
+        Color::White => square.to_index(),
+        Color::Black => square.to_index() ^ 56,
+    };
+    let kind = piece.to_index() * 2 + usize::from(color != perspective);
+
+    orient(king) * 641 + kind * 64 + orient(square) + 1
+}
+
+/// A quantised HalfKP network.
+#[derive(Debug, Clone)]
+pub struct Network {
+    l1: usize,
+    l2: usize,
+    feature_biases: Vec<i16>,
+    feature_weights: Vec<i16>,
+    hidden_biases: Vec<i32>,
+    hidden_weights: Vec<i8>,
+    output_bias: i32,
+    output_weights: Vec<i8>,
+    kernels: Kernels,
+}
+
+impl Network {
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+
+    /// Parses a network in the format described in the [module docs](self).
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let mut reader = Reader(bytes);
+
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(invalid("not an NNUE HalfKP network"));
+        }
+
+        let l1 = reader.u32()? as usize;
+        let l2 = reader.u32()? as usize;
+        if !(1..=MAX_L1).contains(&l1) || !(1..=MAX_L2).contains(&l2) {
+            return Err(invalid("unsupported network dimensions"));
+        }
+
+        let network = Self {
+            l1,
+            l2,
+            feature_biases: reader.i16s(l1)?,
+            feature_weights: reader.i16s(HALFKP_FEATURES * l1)?,
+            hidden_biases: reader.i32s(l2)?,
+            hidden_weights: reader.i8s(l2 * 2 * l1)?,
+            output_bias: reader.i32s(1)?[0],
+            output_weights: reader.i8s(l2)?,
+            kernels: Kernels::detect(),
+        };
+
+        if !reader.0.is_empty() {
+            return Err(invalid("trailing data after network"));
+        }
+
+        Ok(network)
+    }
+
+    /// Sizes of the feature transformer and the hidden layer.
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.l1, self.l2)
+    }
+
+    fn feature_weights(&self, index: usize) -> &[i16] {
+        &self.feature_weights[index * self.l1..(index + 1) * self.l1]
+    }
+
+    /// Evaluation in centipawns for `side_to_move`, from an up to date `accumulator`.
+    pub fn evaluate(&self, accumulator: &Accumulator, side_to_move: Color) -> i32 {
+        let mut input = vec![0u8; 2 * self.l1];
+        let (us, them) = input.split_at_mut(self.l1);
+        simd::clipped_relu(accumulator.values(side_to_move), us);
+        simd::clipped_relu(accumulator.values(!side_to_move), them);
+
+        let output = self
+            .hidden_weights
+            .chunks_exact(2 * self.l1)
+            .zip(&self.hidden_biases)
+            .zip(&self.output_weights)
+            .map(|((row, bias), weight)| {
+                let hidden = ((bias + self.kernels.dot(&input, row)) >> HIDDEN_SHIFT).clamp(0, 127);
+                hidden * *weight as i32
+            })
+            .sum::<i32>()
+            + self.output_bias;
+
+        output / OUTPUT_SCALE
+    }
+
+    /// Evaluation of `board` for the side to move, building the accumulator from scratch.
+    pub fn evaluate_board(&self, board: &Board) -> i32 {
+        self.evaluate(&Accumulator::new(self, board), board.side_to_move())
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        if self.0.len() < len {
+            return Err(invalid("truncated network"));
+        }
+
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn i8s(&mut self, count: usize) -> io::Result<Vec<i8>> {
+        Ok(self.take(count)?.iter().map(|&b| b as i8).collect())
+    }
+
+    fn i16s(&mut self, count: usize) -> io::Result<Vec<i16>> {
+        Ok(self
+            .take(count * 2)?
+            .chunks_exact(2)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]))
+            .collect())
+    }
+
+    fn i32s(&mut self, count: usize) -> io::Result<Vec<i32>> {
+        Ok(self
+            .take(count * 4)?
+            .chunks_exact(4)
+            .map(|b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect())
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Feature transformer output for both sides, indexed by [`Color::to_index`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Accumulator {
+    values: [Vec<i16>; 2],
+}
+
+impl Accumulator {
+    pub fn new(network: &Network, board: &Board) -> Self {
+        let mut accumulator = Self {
+            values: [Vec::new(), Vec::new()],
+        };
+
+        for perspective in ALL_COLORS {
+            accumulator.refresh(network, board, perspective);
+        }
+
+        accumulator
+    }
+
+    pub fn values(&self, perspective: Color) -> &[i16] {
+        &self.values[perspective.to_index()]
+    }
+
+    fn refresh(&mut self, network: &Network, board: &Board, perspective: Color) {
+        let values = &mut self.values[perspective.to_index()];
+        values.clear();
+        values.extend_from_slice(&network.feature_biases);
+
+        let king = board.king_square(perspective);
+        for piece in FEATURE_PIECES {
+            for color in ALL_COLORS {
+                for square in board.pieces(piece) & board.color_combined(color) {
+                    let index = feature_index(perspective, king, piece, color, square);
+                    network
+                        .kernels
+                        .add_assign(values, network.feature_weights(index));
+                }
+            }
+        }
+    }
+
+    /// Brings the accumulator from `before` to `after`. Only the pieces that changed are
+    /// added or removed, unless a king moved, in which case that side is rebuilt.
+    pub fn update(&mut self, network: &Network, before: &Board, after: &Board) {
+        for perspective in ALL_COLORS {
+            let king = after.king_square(perspective);
+            if king != before.king_square(perspective) {
+                self.refresh(network, after, perspective);
+                continue;
+            }
+
+            let values = &mut self.values[perspective.to_index()];
+            for piece in FEATURE_PIECES {
+                for color in ALL_COLORS {
+                    let old = before.pieces(piece) & before.color_combined(color);
+                    let new = after.pieces(piece) & after.color_combined(color);
+
+                    for square in old & !new {
+                        let index = feature_index(perspective, king, piece, color, square);
+                        network
+                            .kernels
+                            .sub_assign(values, network.feature_weights(index));
+                    }
+                    for square in new & !old {
+                        let index = feature_index(perspective, king, piece, color, square);
+                        network
+                            .kernels
+                            .add_assign(values, network.feature_weights(index));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// An [`Accumulator`] kept up to date with the moves played in a
+/// [`GameWrapper`](crate::state::GameWrapper), see
+/// [`GameWrapper::with_network`](crate::state::GameWrapper::with_network).
+#[derive(Debug, Clone)]
+pub struct NnueState {
+    accumulator: Accumulator,
+    network: Arc<Network>,
+}
+
+impl NnueState {
+    pub fn new(network: Arc<Network>, board: &Board) -> Self {
+        Self {
+            accumulator: Accumulator::new(&network, board),
+            network,
+        }
+    }
+
+    pub fn accumulator(&self) -> &Accumulator {
+        &self.accumulator
+    }
+
+    pub fn network(&self) -> &Arc<Network> {
+        &self.network
+    }
+
+    /// Evaluation in centipawns for `side_to_move`.
+    pub fn evaluate(&self, side_to_move: Color) -> i32 {
+        self.network.evaluate(&self.accumulator, side_to_move)
+    }
+
+    pub(crate) fn update(&mut self, before: &Board, after: &Board) {
+        self.accumulator.update(&self.network, before, after);
+    }
+}
+
+/// Evaluation from the point of view of `color` by a [`Network`] alone, the drop-in for
+/// [`ChessEvaluator`](crate::evaluator::ChessEvaluator) in the [`ChessNnueMCTS`] search
+/// and in minimax as [`ChessNnueGame`]. States built with
+/// [`GameWrapper::with_network`] and the same network are scored from their accumulator,
+/// any other state from scratch.
+#[derive(Debug, Clone)]
+pub struct NnueEvaluator {
+    pub color: Color,
+    network: Arc<Network>,
+    win_probability: WinProbability,
+}
+
+impl NnueEvaluator {
+    pub fn new(color: Color, network: Arc<Network>) -> Self {
+        Self {
+            color,
+            network,
+            win_probability: WinProbability::default(),
+        }
+    }
+
+    /// Evaluator using the network in `path`, see [`Network::load`].
+    pub fn from_file(color: Color, path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::new(color, Arc::new(Network::load(path)?)))
+    }
+
+    /// Turns evaluations into rewards with `win_probability`.
+    pub fn with_win_probability(mut self, win_probability: WinProbability) -> Self {
+        self.win_probability = win_probability;
+        self
+    }
+
+    pub fn win_probability(&self) -> WinProbability {
+        self.win_probability
+    }
+
+    pub fn network(&self) -> &Arc<Network> {
+        &self.network
+    }
+
+    /// Network evaluation of `board` in centipawns, ignoring mates and draws.
+    pub fn evaluate_board(&self, board: &Board) -> f64 {
+        self.relative(board, self.network.evaluate_board(board))
+    }
+
+    /// Network evaluation of `position`, from the accumulator it carries when that
+    /// belongs to this network.
+    pub(crate) fn evaluate_position(&self, position: &Position) -> f64 {
+        match position.nnue() {
+            Some(nnue) if Arc::ptr_eq(&self.network, nnue.network()) => {
+                let board = position.board();
+                self.relative(board, nnue.evaluate(board.side_to_move()))
+            }
+            _ => self.evaluate_board(position.board()),
+        }
+    }
+
+    fn evaluate_state(&self, state: &GameWrapper) -> ChessEvaluation {
+        state.outcome().unwrap_or_else(|| {
+            ChessEvaluation::Evaluation(self.evaluate_position(state.position()))
+        })
+    }
+
+    /// `centipawns` for the side to move in `board` from the point of view of the
+    /// evaluator's colour.
+    fn relative(&self, board: &Board, centipawns: i32) -> f64 {
+        match board.side_to_move() == self.color {
+            true => centipawns as f64,
+            false => -centipawns as f64,
+        }
+    }
+
+    pub fn interpret(&self, evaluation: &ChessEvaluation, player: &Color) -> f64 {
+        interpret_evaluation(self.color, evaluation, player, self.win_probability)
+    }
+}
+
+impl Evaluator<ChessNnueMCTS> for NnueEvaluator {
+    type StateEvaluation = ChessEvaluation;
+
+    fn evaluate_new_state(
+        &self,
+        state: &GameWrapper,
+        moves: &mcts::MoveList<ChessNnueMCTS>,
+        _: Option<mcts::SearchHandle<ChessNnueMCTS>>,
+    ) -> (
+        Vec<mcts::MoveEvaluation<ChessNnueMCTS>>,
+        Self::StateEvaluation,
+    ) {
+        let evals = moves.iter().map(|_| ()).collect();
+
+        (evals, self.evaluate_state(state))
+    }
+
+    fn evaluate_existing_state(
+        &self,
+        _state: &GameWrapper,
+        existing_evaln: &Self::StateEvaluation,
+        _handle: mcts::SearchHandle<ChessNnueMCTS>,
+    ) -> Self::StateEvaluation {
+        existing_evaln.clone()
+    }
+
+    fn interpret_evaluation_for_player(
+        &self,
+        evaluation: &Self::StateEvaluation,
+        player: &mcts::Player<ChessNnueMCTS>,
+    ) -> f64 {
+        self.interpret(evaluation, player)
+    }
+}
+
+impl GameEvaluator<ChessNnueGame> for NnueEvaluator {
+    type Evaluation = ChessEvaluation;
+
+    fn evaluate(&self, node: &GameWrapper, _depth: usize) -> Self::Evaluation {
+        self.evaluate_state(node)
+    }
+
+    fn interpret_for_player(&self, evaluation: &Self::Evaluation, player: Color) -> f64 {
+        self.interpret(evaluation, &player)
+    }
+}
@@ -0,0 +1,140 @@
+//! Vectorised kernels for the network. The AVX2 versions are picked when a network is
+//! loaded on a CPU that supports them, everything else falls back to plain loops.
+
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+
+/// The kernels the CPU can run, detected once per network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kernels {
+    Scalar,
+    /// Only built by [`Kernels::detect`], once the CPU is known to support AVX2.
+    #[cfg(target_arch = "x86_64")]
+    Avx2,
+}
+
+impl Kernels {
+    /// The fastest kernels this CPU supports.
+    pub fn detect() -> Self {
+        #[cfg(target_arch = "x86_64")]
+        if is_x86_feature_detected!("avx2") {
+            return Kernels::Avx2;
+        }
+
+        Kernels::Scalar
+    }
+
+    /// `values += weights`, wrapping like the 16 bit SIMD lanes do.
+    pub fn add_assign(self, values: &mut [i16], weights: &[i16]) {
+        assert_eq!(values.len(), weights.len());
+
+        match self {
+            // SAFETY: AVX2 was detected and both slices have the same length.
+            #[cfg(target_arch = "x86_64")]
+            Kernels::Avx2 => unsafe { add_assign_avx2(values, weights) },
+            Kernels::Scalar => add_assign_scalar(values, weights),
+        }
+    }
+
+    /// `values -= weights`, wrapping like the 16 bit SIMD lanes do.
+    pub fn sub_assign(self, values: &mut [i16], weights: &[i16]) {
+        assert_eq!(values.len(), weights.len());
+
+        match self {
+            // SAFETY: AVX2 was detected and both slices have the same length.
+            #[cfg(target_arch = "x86_64")]
+            Kernels::Avx2 => unsafe { sub_assign_avx2(values, weights) },
+            Kernels::Scalar => sub_assign_scalar(values, weights),
+        }
+    }
+
+    /// Dot product of clipped activations with a row of weights. The inputs must lie in
+    /// `0..=127`, so the pairwise sums of `maddubs` can not saturate.
+    pub fn dot(self, input: &[u8], weights: &[i8]) -> i32 {
+        assert_eq!(input.len(), weights.len());
+
+        match self {
+            // SAFETY: AVX2 was detected and both slices have the same length.
+            #[cfg(target_arch = "x86_64")]
+            Kernels::Avx2 => unsafe { dot_avx2(input, weights) },
+            Kernels::Scalar => dot_scalar(input, weights),
+        }
+    }
+}
+
+/// Clamps the accumulator to `0..=127`, the input range of the hidden layer.
+pub fn clipped_relu(values: &[i16], output: &mut [u8]) {
+    for (out, value) in output.iter_mut().zip(values) {
+        *out = (*value).clamp(0, 127) as u8;
+    }
+}
+
+fn add_assign_scalar(values: &mut [i16], weights: &[i16]) {
+    for (value, weight) in values.iter_mut().zip(weights) {
+        *value = value.wrapping_add(*weight);
+    }
+}
+
+fn sub_assign_scalar(values: &mut [i16], weights: &[i16]) {
+    for (value, weight) in values.iter_mut().zip(weights) {
+        *value = value.wrapping_sub(*weight);
+    }
+}
+
+fn dot_scalar(input: &[u8], weights: &[i8]) -> i32 {
+    input
+        .iter()
+        .zip(weights)
+        .map(|(&input, &weight)| input as i32 * weight as i32)
+        .sum()
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn add_assign_avx2(values: &mut [i16], weights: &[i16]) {
+    let chunks = values.len() / 16;
+
+    for i in 0..chunks {
+        let value = values.as_mut_ptr().add(i * 16) as *mut __m256i;
+        let weight = weights.as_ptr().add(i * 16) as *const __m256i;
+        let sum = _mm256_add_epi16(_mm256_loadu_si256(value), _mm256_loadu_si256(weight));
+        _mm256_storeu_si256(value, sum);
+    }
+
+    add_assign_scalar(&mut values[chunks * 16..], &weights[chunks * 16..]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn sub_assign_avx2(values: &mut [i16], weights: &[i16]) {
+    let chunks = values.len() / 16;
+
+    for i in 0..chunks {
+        let value = values.as_mut_ptr().add(i * 16) as *mut __m256i;
+        let weight = weights.as_ptr().add(i * 16) as *const __m256i;
+        let difference = _mm256_sub_epi16(_mm256_loadu_si256(value), _mm256_loadu_si256(weight));
+        _mm256_storeu_si256(value, difference);
+    }
+
+    sub_assign_scalar(&mut values[chunks * 16..], &weights[chunks * 16..]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn dot_avx2(input: &[u8], weights: &[i8]) -> i32 {
+    let chunks = input.len() / 32;
+    let ones = _mm256_set1_epi16(1);
+    let mut sum = _mm256_setzero_si256();
+
+    for i in 0..chunks {
+        let a = _mm256_loadu_si256(input.as_ptr().add(i * 32) as *const __m256i);
+        let b = _mm256_loadu_si256(weights.as_ptr().add(i * 32) as *const __m256i);
+        let products = _mm256_madd_epi16(_mm256_maddubs_epi16(a, b), ones);
+        sum = _mm256_add_epi32(sum, products);
+    }
+
+    let mut lanes = [0i32; 8];
+    _mm256_storeu_si256(lanes.as_mut_ptr() as *mut __m256i, sum);
+
+    lanes.iter().sum::<i32>() + dot_scalar(&input[chunks * 32..], &weights[chunks * 32..])
+}
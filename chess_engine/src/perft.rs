@@ -0,0 +1,43 @@
+//! Move generation checks: counts the leaf nodes of the legal move tree, straight on
+//! the [`Board`] and its [`MoveGen`]. Draws by repetition or the fifty-move rule don't
+//! end a line here, as in the published perft results.
+
+use chess::{Board, ChessMove, MoveGen};
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+
+/// Number of leaf nodes `depth` plies below `board`.
+pub fn perft(board: &Board, depth: usize) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let moves = MoveGen::new_legal(board);
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+
+    moves
+        .map(|mov| perft(&board.make_move_new(mov), depth - 1))
+        .sum()
+}
+
+/// Leaf nodes below every legal move of `board`, in move generation order. The root
+/// moves are split over a pool of `threads` threads.
+pub fn divide(board: &Board, depth: usize, threads: usize) -> Vec<(ChessMove, u64)> {
+    if depth == 0 {
+        return Vec::new();
+    }
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .expect("Could not build thread pool");
+
+    pool.install(|| {
+        MoveGen::new_legal(board)
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .map(|mov| (mov, perft(&board.make_move_new(mov), depth - 1)))
+            .collect()
+    })
+}
@@ -0,0 +1,539 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use chess::{ChessMove, Color, Game};
+use mcts::{
+    transposition_table::ApproxTable, tree_policy::UCTPolicy, GameState, MCTSManager, MoveInfo,
+    NodeHandle, ThreadData, MCTS,
+};
+
+#[cfg(feature = "nnue")]
+use crate::nnue::Network;
+use crate::{
+    book::OpeningBook,
+    evaluator::{
+        params::EvalParams, rollout::LeafEvaluation, ChessEvaluation, ChessEvaluator,
+        WinProbability,
+    },
+    mcts_handler::{ChessMCTS, ChessPuctMCTS, ChessRaveMCTS},
+    state::GameWrapper,
+    tablebase::Tablebase,
+    time_manager::{Clock, TimeManager},
+    tree_policy::{PuctPolicy, RavePolicy},
+    tree_search, SearchAlgorithm, SearchPolicy,
+};
+
+/// Time searched between two checks of the limits and the stop flag.
+const CHECK_INTERVAL: Duration = Duration::from_millis(10);
+/// Playouts per thread between two checks when only the number of playouts is limited.
+const PLAYOUT_BATCH: u32 = 256;
+const INFO_INTERVAL: Duration = Duration::from_secs(1);
+
+/// When to stop searching. Without any limit the search only ends when the stop flag
+/// is raised.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchLimits {
+    pub movetime: Option<Duration>,
+    pub playouts: Option<u64>,
+    /// Size of the search tree.
+    pub nodes: Option<usize>,
+    /// Clock of the side to move, the time to use is left to a [`TimeManager`].
+    pub clock: Option<Clock>,
+}
+
+impl SearchLimits {
+    pub fn is_unlimited(&self) -> bool {
+        *self == SearchLimits::default()
+    }
+}
+
+/// How to search: the algorithm, when to stop, the resources to use and what to look up
+/// instead of searching. Built with the `with_` methods from the defaults.
+#[derive(Clone)]
+pub struct SearchConfig {
+    pub algorithm: SearchAlgorithm,
+    pub limits: SearchLimits,
+    pub threads: usize,
+    /// Capacity of the transposition table.
+    pub table_size: usize,
+    /// Print a performance test before and the root moves after an MCTS search to stderr.
+    pub verbose: bool,
+    pub params: Arc<EvalParams>,
+    /// How MCTS evaluates new leaves.
+    pub leaf_evaluation: LeafEvaluation,
+    /// How MCTS turns evaluations into rewards.
+    pub win_probability: WinProbability,
+    pub book: Option<Arc<OpeningBook>>,
+    pub tablebase: Option<Arc<dyn Tablebase>>,
+    /// Network that evaluates positions instead of the handcrafted terms.
+    #[cfg(feature = "nnue")]
+    pub network: Option<Arc<Network>>,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        SearchConfig {
+            algorithm: SearchAlgorithm::default(),
+            limits: SearchLimits::default(),
+            threads: std::thread::available_parallelism().map_or(1, |n| n.get()),
+            table_size: 1024,
+            verbose: false,
+            params: Arc::new(EvalParams::default()),
+            leaf_evaluation: LeafEvaluation::default(),
+            win_probability: WinProbability::default(),
+            book: None,
+            tablebase: None,
+            #[cfg(feature = "nnue")]
+            network: None,
+        }
+    }
+}
+
+impl SearchConfig {
+    pub fn with_algorithm(mut self, algorithm: SearchAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Sets the exploration constant of the MCTS policy, minimax has none.
+    pub fn with_exploration(mut self, exploration: f64) -> Self {
+        if let SearchAlgorithm::Mcts(policy) = self.algorithm {
+            self.algorithm = SearchAlgorithm::Mcts(policy.with_exploration(exploration));
+        }
+        self
+    }
+
+    pub fn with_limits(mut self, limits: SearchLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn with_movetime(mut self, movetime: Duration) -> Self {
+        self.limits.movetime = Some(movetime);
+        self
+    }
+
+    pub fn with_playouts(mut self, playouts: u64) -> Self {
+        self.limits.playouts = Some(playouts);
+        self
+    }
+
+    pub fn with_nodes(mut self, nodes: usize) -> Self {
+        self.limits.nodes = Some(nodes);
+        self
+    }
+
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.limits.clock = Some(clock);
+        self
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    pub fn with_table_size(mut self, table_size: usize) -> Self {
+        self.table_size = table_size;
+        self
+    }
+
+    pub fn with_verbose(mut self, verbose: bool) -> Self {
+        self.verbose = verbose;
+        self
+    }
+
+    pub fn with_params(mut self, params: Arc<EvalParams>) -> Self {
+        self.params = params;
+        self
+    }
+
+    pub fn with_leaf_evaluation(mut self, leaf_evaluation: LeafEvaluation) -> Self {
+        self.leaf_evaluation = leaf_evaluation;
+        self
+    }
+
+    pub fn with_win_probability(mut self, win_probability: WinProbability) -> Self {
+        self.win_probability = win_probability;
+        self
+    }
+
+    pub fn with_book(mut self, book: Arc<OpeningBook>) -> Self {
+        self.book = Some(book);
+        self
+    }
+
+    pub fn with_tablebase(mut self, tablebase: Arc<dyn Tablebase>) -> Self {
+        self.tablebase = Some(tablebase);
+        self
+    }
+
+    #[cfg(feature = "nnue")]
+    pub fn with_network(mut self, network: Arc<Network>) -> Self {
+        self.network = Some(network);
+        self
+    }
+
+    /// The evaluator for a search by `color` with these parameters, tablebase, network,
+    /// leaf evaluation and win probability.
+    pub fn evaluator(&self, color: Color) -> ChessEvaluator {
+        let mut evaluator = ChessEvaluator::with_params(color, self.params.clone())
+            .with_leaf_evaluation(self.leaf_evaluation)
+            .with_win_probability(self.win_probability);
+        if let Some(tablebase) = &self.tablebase {
+            evaluator = evaluator.with_tablebase(tablebase.clone());
+        }
+        #[cfg(feature = "nnue")]
+        if let Some(network) = &self.network {
+            evaluator = evaluator.with_network(network.clone());
+        }
+
+        evaluator
+    }
+
+    /// The root state of a search of `game`, carrying the accumulator of the network.
+    pub fn root_state(&self, game: &Game) -> GameWrapper {
+        let root = GameWrapper::new(game.clone());
+        #[cfg(feature = "nnue")]
+        let root = match &self.network {
+            Some(network) => root.with_network(network.clone()),
+            None => root,
+        };
+
+        root
+    }
+}
+
+/// Progress of a running search, as reported to protocol front-ends.
+#[derive(Debug, Clone)]
+pub struct SearchInfo {
+    pub playouts: u64,
+    pub tree_nodes: usize,
+    pub elapsed: Duration,
+    /// Average evaluation of the most visited root move in centipawns, for the side to move.
+    pub score: Option<f64>,
+    pub pv: Vec<ChessMove>,
+}
+
+/// Where the move of a [`SearchReport`] came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveSource {
+    Search,
+    Book,
+    Tablebase,
+}
+
+/// Outcome of a finished search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchReport {
+    pub best_move: Option<ChessMove>,
+    /// Evaluation of the best move in centipawns for the side to move, when known.
+    pub score: Option<f64>,
+    pub pv: Vec<ChessMove>,
+    /// Visits of every root move, empty unless the move was found by MCTS.
+    pub root_visits: Vec<(ChessMove, u64)>,
+    /// Visits of the searched position kept from an earlier search of it.
+    pub reused_visits: u64,
+    pub playouts: u64,
+    pub elapsed: Duration,
+    pub source: MoveSource,
+}
+
+impl SearchReport {
+    /// Report of a move that was looked up instead of searched.
+    pub fn lookup(mov: ChessMove, source: MoveSource) -> Self {
+        SearchReport {
+            best_move: Some(mov),
+            score: None,
+            pv: vec![mov],
+            root_visits: Vec::new(),
+            reused_visits: 0,
+            playouts: 0,
+            elapsed: Duration::ZERO,
+            source,
+        }
+    }
+}
+
+/// Searches `game` for the side to move as configured by `config`, until a limit is hit
+/// or `stop` is raised; minimax also stops at its depth and ignores the playout and node
+/// limits. `on_info` is called about once per second and once when the search ends.
+pub fn search<F>(game: &Game, config: &SearchConfig, stop: &AtomicBool, on_info: F) -> SearchReport
+where
+    F: FnMut(&SearchInfo),
+{
+    let color = game.side_to_move();
+    let evaluator = config.evaluator(color);
+
+    match config.algorithm {
+        SearchAlgorithm::Mcts(SearchPolicy::Uct { exploration }) => run_limited(
+            &mut MCTSManager::new(
+                config.root_state(game),
+                ChessMCTS,
+                evaluator,
+                UCTPolicy::new(exploration),
+                ApproxTable::new(config.table_size),
+            ),
+            config,
+            stop,
+            on_info,
+        ),
+        SearchAlgorithm::Mcts(SearchPolicy::Rave {
+            exploration,
+            equivalence,
+        }) => run_limited(
+            &mut MCTSManager::new(
+                config.root_state(game),
+                ChessRaveMCTS(color, config.win_probability),
+                evaluator,
+                RavePolicy::new(exploration, equivalence),
+                ApproxTable::new(config.table_size),
+            ),
+            config,
+            stop,
+            on_info,
+        ),
+        SearchAlgorithm::Mcts(SearchPolicy::Puct { exploration }) => run_limited(
+            &mut MCTSManager::new(
+                config.root_state(game),
+                ChessPuctMCTS,
+                evaluator,
+                PuctPolicy::new(exploration),
+                ApproxTable::new(config.table_size),
+            ),
+            config,
+            stop,
+            on_info,
+        ),
+        SearchAlgorithm::MiniMax { depth } => {
+            let start = Instant::now();
+            let time = config.limits.clock.as_ref().map(TimeManager::new);
+            let movetime = config.limits.movetime;
+            // No deeper search starts after the soft limit, and none runs past the hard one.
+            let soft_limit = movetime
+                .into_iter()
+                .chain(time.as_ref().map(TimeManager::soft_limit))
+                .min();
+            let hard_limit = movetime
+                .into_iter()
+                .chain(time.as_ref().map(TimeManager::hard_limit))
+                .min();
+            let should_stop = || {
+                stop.load(Ordering::Relaxed)
+                    || hard_limit.is_some_and(|limit| start.elapsed() >= limit)
+            };
+            let should_deepen = || soft_limit.is_none_or(|limit| start.elapsed() < limit);
+
+            let result = tree_search::find_mini_max_move(
+                &config.root_state(game),
+                depth,
+                config.threads,
+                &evaluator,
+                should_stop,
+                should_deepen,
+            );
+            let score = result.as_ref().and_then(|result| match result.evaluation {
+                ChessEvaluation::Evaluation(score) => Some(score),
+                ChessEvaluation::Draw(_) => Some(0.0),
+                ChessEvaluation::Winner(_) => None,
+            });
+
+            SearchReport {
+                best_move: result.as_ref().map(|result| result.found_move),
+                score,
+                pv: result.iter().map(|result| result.found_move).collect(),
+                root_visits: Vec::new(),
+                reused_visits: 0,
+                playouts: 0,
+                elapsed: start.elapsed(),
+                source: MoveSource::Search,
+            }
+        }
+    }
+}
+
+/// Runs `manager` until a limit in `config` is hit or `stop` is raised.
+pub(crate) fn run_limited<Spec, F>(
+    manager: &mut MCTSManager<Spec>,
+    config: &SearchConfig,
+    stop: &AtomicBool,
+    mut on_info: F,
+) -> SearchReport
+where
+    Spec: MCTS,
+    Spec::State: GameState<Move = ChessMove>,
+    ThreadData<Spec>: Default,
+    F: FnMut(&SearchInfo),
+{
+    let cores = config.threads.max(1);
+    let limits = &config.limits;
+
+    if config.verbose {
+        manager.perf_test_to_stderr(cores);
+    }
+
+    let start = Instant::now();
+    let mut last_info = start;
+    let mut playouts = 0u64;
+    let mut time = limits.clock.as_ref().map(TimeManager::new);
+    let deadline = limits
+        .movetime
+        .into_iter()
+        .chain(time.as_ref().map(TimeManager::hard_limit))
+        .min();
+
+    loop {
+        // Without a deadline a playout limit is met exactly, by counting out batches.
+        let batch = match (limits.playouts, deadline) {
+            (Some(max), None) => Some(
+                max.saturating_sub(playouts)
+                    .min(PLAYOUT_BATCH as u64 * cores as u64) as u32,
+            ),
+            _ => None,
+        };
+
+        match batch {
+            Some(batch) => {
+                manager.playout_n_parallel(batch, cores);
+                playouts += batch as u64;
+            }
+            None => {
+                // Long rollouts make the time of a playout unpredictable, so the search
+                // runs in short slices that never reach past the deadline.
+                let slice = deadline.map_or(CHECK_INTERVAL, |deadline| {
+                    deadline.saturating_sub(start.elapsed()).min(CHECK_INTERVAL)
+                });
+                let visits = total_visits(manager.tree().root_node());
+                manager.playout_parallel_for(slice, cores);
+                playouts += total_visits(manager.tree().root_node()) - visits;
+            }
+        }
+
+        let elapsed = start.elapsed();
+        let done = stop.load(Ordering::Relaxed)
+            || batch == Some(0)
+            || limits.movetime.is_some_and(|movetime| elapsed >= movetime)
+            || limits.playouts.is_some_and(|max| playouts >= max)
+            || limits
+                .nodes
+                .is_some_and(|max| manager.tree().num_nodes() >= max)
+            || time.as_mut().is_some_and(|time| {
+                let root = manager.tree().root_node();
+                time.should_stop(
+                    elapsed,
+                    &root_visits(root),
+                    root_score(root, config.win_probability),
+                )
+            });
+
+        if done || last_info.elapsed() >= INFO_INTERVAL {
+            last_info = Instant::now();
+            on_info(&search_info(manager, config, playouts, elapsed));
+        }
+
+        if done {
+            break;
+        }
+    }
+
+    if config.verbose {
+        manager.tree().display_moves();
+    }
+
+    let info = search_info(manager, config, playouts, start.elapsed());
+    let root = manager.tree().root_node();
+    SearchReport {
+        best_move: root
+            .moves()
+            .max_by_key(|info| info.visits())
+            .map(|info| *info.get_move()),
+        score: info.score,
+        pv: info.pv,
+        root_visits: root_visits(root),
+        reused_visits: 0,
+        playouts,
+        elapsed: info.elapsed,
+        source: MoveSource::Search,
+    }
+}
+
+fn search_info<Spec>(
+    manager: &MCTSManager<Spec>,
+    config: &SearchConfig,
+    playouts: u64,
+    elapsed: Duration,
+) -> SearchInfo
+where
+    Spec: MCTS,
+    Spec::State: GameState<Move = ChessMove>,
+    ThreadData<Spec>: Default,
+{
+    let root = manager.tree().root_node();
+    SearchInfo {
+        playouts,
+        tree_nodes: manager.tree().num_nodes(),
+        elapsed,
+        score: root_score(root, config.win_probability),
+        pv: principal_variation(root, 16),
+    }
+}
+
+/// Visits of all moves from `root` together.
+fn total_visits<Spec: MCTS>(root: NodeHandle<Spec>) -> u64 {
+    root.moves().map(|info| info.visits()).sum()
+}
+
+/// Visits of every move from `root`.
+pub(crate) fn root_visits<Spec>(root: NodeHandle<Spec>) -> Vec<(ChessMove, u64)>
+where
+    Spec: MCTS,
+    Spec::State: GameState<Move = ChessMove>,
+{
+    root.moves()
+        .map(|info| (*info.get_move(), info.visits()))
+        .collect()
+}
+
+/// Average win probability of the most visited move from `root` as centipawns, for the
+/// side to move there.
+fn root_score<Spec>(root: NodeHandle<Spec>, win_probability: WinProbability) -> Option<f64>
+where
+    Spec: MCTS,
+    Spec::State: GameState<Move = ChessMove>,
+{
+    most_visited(root)
+        .map(|info| win_probability.centipawns(info.sum_rewards() / info.visits() as f64))
+}
+
+/// Follows the most visited moves from `root`, for at most `num_moves` plies.
+fn principal_variation<Spec>(root: NodeHandle<Spec>, num_moves: usize) -> Vec<ChessMove>
+where
+    Spec: MCTS,
+    Spec::State: GameState<Move = ChessMove>,
+{
+    let mut pv = Vec::new();
+    let mut node = Some(root);
+    while let Some(info) = node.and_then(most_visited) {
+        if pv.len() >= num_moves {
+            break;
+        }
+        pv.push(*info.get_move());
+        node = info.child();
+    }
+
+    pv
+}
+
+fn most_visited<'a, Spec>(node: NodeHandle<'a, Spec>) -> Option<&'a MoveInfo<Spec>>
+where
+    Spec: MCTS,
+{
+    node.moves()
+        .filter(|info| info.visits() > 0)
+        .max_by_key(|info| info.visits())
+}
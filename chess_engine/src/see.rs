@@ -0,0 +1,127 @@
+//! Static exchange evaluation: the material outcome of all captures on one square,
+//! with both sides always recapturing with their least valuable attacker and free to
+//! stop when recapturing would lose material. Pins are not taken into account.
+
+use chess::{
+    get_bishop_moves, get_king_moves, get_knight_moves, get_pawn_attacks, get_rook_moves, BitBoard,
+    Board, ChessMove, Color, Piece, Square, EMPTY,
+};
+
+/// Centipawn value of `piece` in an exchange.
+pub fn piece_value(piece: Piece) -> i32 {
+    match piece {
+        Piece::Pawn => 100,
+        Piece::Knight => 320,
+        Piece::Bishop => 330,
+        Piece::Rook => 500,
+        Piece::Queen => 900,
+        Piece::King => 20000,
+    }
+}
+
+/// Material won by the side to move when it plays `mov` and the exchange on the
+/// destination square is played out, in centipawns. Sliders behind the pieces that
+/// capture (x-rays) join the exchange once the square is opened for them.
+pub fn see(board: &Board, mov: ChessMove) -> i32 {
+    let source = mov.get_source();
+    let target = mov.get_dest();
+    let mut occupied = *board.combined();
+    let mut gains = Vec::with_capacity(32);
+
+    let (mut attacker, first_gain) = match board.piece_on(source) {
+        Some(Piece::Pawn)
+            if board.piece_on(target).is_none() && source.get_file() != target.get_file() =>
+        {
+            // En passant: the captured pawn is not on the target square.
+            occupied ^=
+                BitBoard::from_square(Square::make_square(source.get_rank(), target.get_file()));
+            (Piece::Pawn, piece_value(Piece::Pawn))
+        }
+        Some(piece) => (piece, board.piece_on(target).map_or(0, piece_value)),
+        None => return 0,
+    };
+
+    let mut gain = first_gain;
+    if let Some(promotion) = mov.get_promotion() {
+        gain += piece_value(promotion) - piece_value(Piece::Pawn);
+        attacker = promotion;
+    }
+    gains.push(gain);
+
+    let mut side = !board.side_to_move();
+    let mut from = BitBoard::from_square(source);
+
+    loop {
+        occupied ^= from;
+
+        let attackers = attackers_to(board, target, occupied) & occupied;
+        let own = attackers & board.color_combined(side);
+        if own == EMPTY {
+            break;
+        }
+
+        let (piece, square) = least_valuable(board, own);
+        if piece == Piece::King && attackers & board.color_combined(!side) != EMPTY {
+            // The king may not recapture into an attacked square.
+            break;
+        }
+
+        // The side to move takes the last capturer and risks its own piece.
+        gains.push(piece_value(attacker) - gains[gains.len() - 1]);
+        attacker = piece;
+        from = BitBoard::from_square(square);
+        side = !side;
+    }
+
+    // Each side only continues the exchange when it gains from doing so.
+    while gains.len() > 1 {
+        let last = gains.pop().unwrap();
+        let previous = gains.last_mut().unwrap();
+        *previous = -(-*previous).max(last);
+    }
+
+    gains[0]
+}
+
+/// Whether `mov` does not lose more than `threshold` centipawns in the exchange.
+pub fn see_ge(board: &Board, mov: ChessMove, threshold: i32) -> bool {
+    see(board, mov) >= threshold
+}
+
+/// Pieces of both colours that attack `square` when only `occupied` blocks sliders.
+fn attackers_to(board: &Board, square: Square, occupied: BitBoard) -> BitBoard {
+    let pawns = *board.pieces(Piece::Pawn);
+    let bishops = board.pieces(Piece::Bishop) | board.pieces(Piece::Queen);
+    let rooks = board.pieces(Piece::Rook) | board.pieces(Piece::Queen);
+
+    (get_pawn_attacks(
+        square,
+        Color::Black,
+        pawns & board.color_combined(Color::White),
+    )) | get_pawn_attacks(
+        square,
+        Color::White,
+        pawns & board.color_combined(Color::Black),
+    ) | (get_knight_moves(square) & board.pieces(Piece::Knight))
+        | (get_king_moves(square) & board.pieces(Piece::King))
+        | (get_bishop_moves(square, occupied) & bishops)
+        | (get_rook_moves(square, occupied) & rooks)
+}
+
+fn least_valuable(board: &Board, attackers: BitBoard) -> (Piece, Square) {
+    [
+        Piece::Pawn,
+        Piece::Knight,
+        Piece::Bishop,
+        Piece::Rook,
+        Piece::Queen,
+        Piece::King,
+    ]
+    .into_iter()
+    .find_map(|piece| {
+        (board.pieces(piece) & attackers)
+            .next()
+            .map(|square| (piece, square))
+    })
+    .expect("attackers is not empty")
+}
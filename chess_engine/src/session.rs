@@ -0,0 +1,181 @@
+//! Searching the positions of one game with a persistent MCTS tree. The `mcts` crate
+//! has no way to make a node the root of its tree, nor to seed a new tree with the
+//! visits of an old one, so the tree is only kept while the same position is searched
+//! again, for instance when a `go` follows a `stop`. Any other position starts a new
+//! tree, which frees the old one.
+
+use std::sync::atomic::AtomicBool;
+
+use chess::Game;
+use mcts::{transposition_table::ApproxTable, tree_policy::UCTPolicy, MCTSManager};
+
+use crate::{
+    lookup_move,
+    mcts_handler::{ChessMCTS, ChessPuctMCTS, ChessRaveMCTS},
+    search::{self, run_limited, SearchConfig, SearchInfo, SearchLimits, SearchReport},
+    tree_policy::{PuctPolicy, RavePolicy},
+    SearchAlgorithm, SearchPolicy, DEFAULT_PLAYOUTS,
+};
+
+enum SessionTree {
+    Uct(MCTSManager<ChessMCTS>),
+    Rave(MCTSManager<ChessRaveMCTS>),
+    Puct(MCTSManager<ChessPuctMCTS>),
+}
+
+struct SessionState {
+    tree: SessionTree,
+    policy: SearchPolicy,
+    /// The game at the root of the tree.
+    root: Game,
+}
+
+/// Searches the positions of one game, keeping the MCTS tree while the position stays.
+pub struct SearchSession {
+    config: SearchConfig,
+    state: Option<SessionState>,
+}
+
+impl SearchSession {
+    pub fn new(config: SearchConfig) -> Self {
+        SearchSession {
+            config,
+            state: None,
+        }
+    }
+
+    pub fn config(&self) -> &SearchConfig {
+        &self.config
+    }
+
+    /// Changes when to stop the next searches; the tree is kept.
+    pub fn set_limits(&mut self, limits: SearchLimits) {
+        self.config.limits = limits;
+    }
+
+    /// Searches with `config` from now on. A change of policy starts a new tree, but the
+    /// tree is kept otherwise, so [`clear`](Self::clear) it when the evaluation changed.
+    pub fn set_config(&mut self, config: SearchConfig) {
+        self.config = config;
+    }
+
+    /// Drops the tree, so the next search starts from scratch.
+    pub fn clear(&mut self) {
+        self.state = None;
+    }
+
+    /// Like [`find_move`](crate::find_move), but reusing the tree of earlier searches.
+    pub fn find_move(&mut self, game: &Game) -> SearchReport {
+        match self.config.limits.is_unlimited() {
+            true => {
+                let limits = self.config.limits.clone();
+                self.config.limits.playouts = Some(DEFAULT_PLAYOUTS);
+                let report = self.search(game, &AtomicBool::new(false), |_| {});
+                self.config.limits = limits;
+                report
+            }
+            false => self.search(game, &AtomicBool::new(false), |_| {}),
+        }
+    }
+
+    /// Like [`search::search`], but continuing the tree of the last search when `game`
+    /// is the position it searched. The visits kept are in
+    /// [`SearchReport::reused_visits`]. A book or tablebase move is played without
+    /// searching, as in [`find_move`](crate::find_move).
+    pub fn search<F>(&mut self, game: &Game, stop: &AtomicBool, on_info: F) -> SearchReport
+    where
+        F: FnMut(&SearchInfo),
+    {
+        if let Some(report) = lookup_move(game, &self.config) {
+            return report;
+        }
+
+        let policy = match self.config.algorithm {
+            SearchAlgorithm::Mcts(policy) => policy,
+            SearchAlgorithm::MiniMax { .. } => {
+                self.state = None;
+                return search::search(game, &self.config, stop, on_info);
+            }
+        };
+
+        let reused_visits = self
+            .state
+            .as_ref()
+            .filter(|state| state.policy == policy && state.searches(game))
+            .map(SessionState::root_visits);
+        let reused_visits = match reused_visits {
+            Some(visits) => visits,
+            None => {
+                self.state = Some(SessionState::new(game, policy, &self.config));
+                0
+            }
+        };
+
+        let state = self.state.as_mut().expect("session has a tree");
+        let mut report = match &mut state.tree {
+            SessionTree::Uct(manager) => run_limited(manager, &self.config, stop, on_info),
+            SessionTree::Rave(manager) => run_limited(manager, &self.config, stop, on_info),
+            SessionTree::Puct(manager) => run_limited(manager, &self.config, stop, on_info),
+        };
+        report.reused_visits = reused_visits;
+
+        report
+    }
+}
+
+impl SessionState {
+    fn new(game: &Game, policy: SearchPolicy, config: &SearchConfig) -> Self {
+        let color = game.side_to_move();
+        let evaluator = config.evaluator(color);
+
+        let tree = match policy {
+            SearchPolicy::Uct { exploration } => SessionTree::Uct(MCTSManager::new(
+                config.root_state(game),
+                ChessMCTS,
+                evaluator,
+                UCTPolicy::new(exploration),
+                ApproxTable::new(config.table_size),
+            )),
+            SearchPolicy::Rave {
+                exploration,
+                equivalence,
+            } => SessionTree::Rave(MCTSManager::new(
+                config.root_state(game),
+                ChessRaveMCTS(color, config.win_probability),
+                evaluator,
+                RavePolicy::new(exploration, equivalence),
+                ApproxTable::new(config.table_size),
+            )),
+            SearchPolicy::Puct { exploration } => SessionTree::Puct(MCTSManager::new(
+                config.root_state(game),
+                ChessPuctMCTS,
+                evaluator,
+                PuctPolicy::new(exploration),
+                ApproxTable::new(config.table_size),
+            )),
+        };
+
+        SessionState {
+            tree,
+            policy,
+            root: game.clone(),
+        }
+    }
+
+    /// Whether the root of the tree is the position of `game`, reached the same way.
+    fn searches(&self, game: &Game) -> bool {
+        self.root.actions() == game.actions()
+            && self.root.current_position() == game.current_position()
+    }
+
+    /// Visits made from the root of the tree so far.
+    fn root_visits(&self) -> u64 {
+        let visits = match &self.tree {
+            SessionTree::Uct(manager) => search::root_visits(manager.tree().root_node()),
+            SessionTree::Rave(manager) => search::root_visits(manager.tree().root_node()),
+            SessionTree::Puct(manager) => search::root_visits(manager.tree().root_node()),
+        };
+
+        visits.iter().map(|(_, visits)| visits).sum()
+    }
+}
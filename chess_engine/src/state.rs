@@ -1,20 +1,250 @@
-use chess::{Action, ChessMove, Color, Game, GameResult, MoveGen};
+use chess::{Action, Board, BoardStatus, ChessMove, Game, MoveGen, Piece, ALL_COLORS};
 use mcts::GameState;
+use search_tree::GameNode;
 use std::hash::Hash;
+#[cfg(feature = "nnue")]
+use std::sync::Arc;
 
+use crate::evaluator::ChessEvaluation;
+#[cfg(feature = "nnue")]
+use crate::nnue::{Network, NnueState};
+
+/// Why a game ended in a draw.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DrawReason {
+    Stalemate,
+    ThreefoldRepetition,
+    FiftyMoveRule,
+    InsufficientMaterial,
+    /// Offered and accepted, or declared through [`Game::declare_draw`].
+    Agreement,
+    /// Drawn with best play according to the endgame tablebases.
+    Tablebase,
+}
+
+/// A board and the draw bookkeeping of the moves that led to it, without the record of
+/// the game. It is cheap to clone and to play on, which is what rollouts do.
+#[derive(Debug, Clone)]
+pub struct Position {
+    board: Board,
+    /// Plies since the last capture or pawn move.
+    halfmove_clock: u32,
+    /// Hashes of the positions since the last capture, pawn move or change of castling
+    /// rights, the current one last.
+    positions: Vec<u64>,
+    /// Accumulator of the network that evaluates the position, updated move by move.
+    #[cfg(feature = "nnue")]
+    nnue: Option<NnueState>,
+}
+
+impl Position {
+    /// `board` with no history before it.
+    pub fn new(board: Board) -> Self {
+        Self {
+            board,
+            halfmove_clock: 0,
+            positions: vec![board.get_hash()],
+            #[cfg(feature = "nnue")]
+            nnue: None,
+        }
+    }
+
+    /// The current board.
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+
+    #[cfg(feature = "nnue")]
+    pub fn nnue(&self) -> Option<&NnueState> {
+        self.nnue.as_ref()
+    }
+
+    /// Plays the legal move `mov`.
+    pub(crate) fn push(&mut self, mov: ChessMove) {
+        let before = self.board;
+        self.board = before.make_move_new(mov);
+        #[cfg(feature = "nnue")]
+        if let Some(nnue) = &mut self.nnue {
+            nnue.update(&before, &self.board);
+        }
+
+        let zeroing = before.piece_on(mov.get_source()) == Some(Piece::Pawn)
+            || before.piece_on(mov.get_dest()).is_some();
+        let castling_changed = ALL_COLORS
+            .iter()
+            .any(|&color| before.castle_rights(color) != self.board.castle_rights(color));
+
+        match zeroing {
+            true => self.halfmove_clock = 0,
+            false => self.halfmove_clock += 1,
+        }
+        // No earlier position can repeat once castling rights are lost.
+        if zeroing || castling_changed {
+            self.positions.clear();
+        }
+        self.positions.push(self.board.get_hash());
+    }
+
+    /// Draw that ends the game in the current position, other than stalemate.
+    pub fn draw_reason(&self) -> Option<DrawReason> {
+        let current = self.board.get_hash();
+
+        if self
+            .positions
+            .iter()
+            .filter(|&&hash| hash == current)
+            .count()
+            >= 3
+        {
+            Some(DrawReason::ThreefoldRepetition)
+        } else if self.halfmove_clock >= 100 {
+            Some(DrawReason::FiftyMoveRule)
+        } else if insufficient_material(&self.board) {
+            Some(DrawReason::InsufficientMaterial)
+        } else {
+            None
+        }
+    }
+
+    /// Result of the game if the board ends it. Checkmate takes precedence over the draw
+    /// rules.
+    pub fn outcome(&self) -> Option<ChessEvaluation> {
+        match self.board.status() {
+            BoardStatus::Checkmate => Some(ChessEvaluation::Winner(!self.board.side_to_move())),
+            BoardStatus::Stalemate => Some(ChessEvaluation::Draw(DrawReason::Stalemate)),
+            BoardStatus::Ongoing => self.draw_reason().map(ChessEvaluation::Draw),
+        }
+    }
+}
+
+/// A [`Game`] that keeps track of its current [`Position`], so draws by repetition and by
+/// the fifty-move rule are noticed during search instead of only when claimed.
 #[derive(Debug, Clone)]
-pub struct GameWrapper(pub Game);
+pub struct GameWrapper {
+    game: Game,
+    position: Position,
+}
+
+impl GameWrapper {
+    /// Wraps `game`. The history before it is only known when the game started from the
+    /// standard position, a game set up from a FEN starts counting at its current
+    /// position.
+    pub fn new(game: Game) -> Self {
+        let board = game.current_position();
+        let mut replay = Position::new(Board::default());
+        for action in game.actions() {
+            if let Action::MakeMove(mov) = action {
+                if !replay.board.legal(*mov) {
+                    return Self::with_position(game, Position::new(board));
+                }
+                replay.push(*mov);
+            }
+        }
+
+        if replay.board.get_hash() == board.get_hash() {
+            Self::with_position(game, replay)
+        } else {
+            Self::with_position(game, Position::new(board))
+        }
+    }
+
+    fn with_position(game: Game, position: Position) -> Self {
+        Self { game, position }
+    }
+
+    /// Keeps the accumulator of `network` up to date with the moves made from here on.
+    #[cfg(feature = "nnue")]
+    pub fn with_network(mut self, network: Arc<Network>) -> Self {
+        self.position.nnue = Some(NnueState::new(network, &self.position.board));
+        self
+    }
+
+    #[cfg(feature = "nnue")]
+    pub fn nnue(&self) -> Option<&NnueState> {
+        self.position.nnue()
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// The current position and its draw bookkeeping.
+    pub fn position(&self) -> &Position {
+        &self.position
+    }
+
+    /// The current board, without replaying the game.
+    pub fn board(&self) -> &Board {
+        self.position.board()
+    }
+
+    pub fn halfmove_clock(&self) -> u32 {
+        self.position.halfmove_clock()
+    }
+
+    /// Draw that ends the game in the current position, other than stalemate.
+    pub fn draw_reason(&self) -> Option<DrawReason> {
+        self.position.draw_reason()
+    }
+
+    /// Result of the game if it is over. Checkmate takes precedence over the draw rules.
+    pub fn outcome(&self) -> Option<ChessEvaluation> {
+        if self.position.board.status() == BoardStatus::Ongoing {
+            match self.game.actions().last() {
+                Some(Action::Resign(color)) => return Some(ChessEvaluation::Winner(!*color)),
+                Some(Action::AcceptDraw) | Some(Action::DeclareDraw) => {
+                    return Some(ChessEvaluation::Draw(DrawReason::Agreement))
+                }
+                _ => {}
+            }
+        }
+
+        self.position.outcome()
+    }
+}
+
+/// Neither side can mate: bare kings, a single minor piece, or only bishops that all
+/// stand on squares of the same colour.
+fn insufficient_material(board: &Board) -> bool {
+    let heavy = board.pieces(Piece::Pawn) | board.pieces(Piece::Rook) | board.pieces(Piece::Queen);
+    if heavy.popcnt() > 0 {
+        return false;
+    }
+
+    let knights = board.pieces(Piece::Knight).popcnt();
+    let bishops = *board.pieces(Piece::Bishop);
+
+    match (knights, bishops.popcnt()) {
+        (0, 0) | (1, 0) => true,
+        (0, _) => {
+            const LIGHT_SQUARES: u64 = 0x55aa_55aa_55aa_55aa;
+            let light = (bishops.0 & LIGHT_SQUARES).count_ones();
+            light == 0 || light == bishops.popcnt()
+        }
+        _ => false,
+    }
+}
 
 impl Default for GameWrapper {
     fn default() -> Self {
-        Self(Game::new())
+        Self::new(Game::new())
+    }
+}
+
+impl From<Game> for GameWrapper {
+    fn from(game: Game) -> Self {
+        Self::new(game)
     }
 }
 
 impl Hash for GameWrapper {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         let mut count = 0;
-        for action in self.0.actions() {
+        for action in self.game.actions() {
             if let Action::MakeMove(m) = action {
                 state.write_u8(m.get_source().to_int());
                 state.write_u8(m.get_dest().to_int());
@@ -23,7 +253,7 @@ impl Hash for GameWrapper {
             }
         }
         state.write_usize(count);
-        state.write_u64(self.0.current_position().get_hash());
+        state.write_u64(self.position.board.get_hash());
     }
 }
 
@@ -33,41 +263,50 @@ impl GameState for GameWrapper {
     type MoveList = Vec<ChessMove>;
 
     fn current_player(&self) -> Self::Player {
-        self.0.side_to_move()
+        self.position.board.side_to_move()
     }
 
     fn available_moves(&self) -> Self::MoveList {
-        MoveGen::new_legal(&self.0.current_position()).collect()
+        MoveGen::new_legal(&self.position.board).collect()
     }
 
     fn make_move(&mut self, mov: &Self::Move) {
-        self.0.make_move(mov.clone());
+        self.game.make_move(*mov);
+        self.position.push(*mov);
     }
 
     fn get_winner(&self) -> Option<Self::Player> {
-        let result = self.0.result();
-
-        match result {
-            Some(GameResult::WhiteCheckmates) => Some(Color::White),
-            Some(GameResult::WhiteResigns) => Some(Color::Black),
-            Some(GameResult::BlackCheckmates) => Some(Color::Black),
-            Some(GameResult::BlackResigns) => Some(Color::White),
+        match self.outcome() {
+            Some(ChessEvaluation::Winner(color)) => Some(color),
             _ => None,
         }
     }
 
     fn is_terminal(&self) -> bool {
-        self.0
-            .result()
-            .map(|result| match result {
-                GameResult::WhiteCheckmates => true,
-                GameResult::WhiteResigns => true,
-                GameResult::BlackCheckmates => true,
-                GameResult::BlackResigns => true,
-                GameResult::Stalemate => true,
-                GameResult::DrawAccepted => true,
-                GameResult::DrawDeclared => false,
-            })
-            .unwrap_or(false)
+        self.outcome().is_some()
+    }
+}
+
+impl GameNode for GameWrapper {
+    type Move = ChessMove;
+    type TerminalResult = ChessEvaluation;
+    type Player = chess::Color;
+
+    fn current_player(&self) -> Self::Player {
+        self.position.board.side_to_move()
+    }
+
+    fn legal_moves(&self) -> Vec<Self::Move> {
+        GameState::available_moves(self)
+    }
+
+    fn is_terminal(&self) -> Option<Self::TerminalResult> {
+        self.outcome()
+    }
+
+    fn make_move(&self, m: Self::Move) -> Self {
+        let mut wrapper = self.clone();
+        GameState::make_move(&mut wrapper, &m);
+        wrapper
     }
 }
@@ -0,0 +1,247 @@
+//! Endgame tablebase probing.
+//!
+//! The search talks to tablebases through the [`Tablebase`] trait: won and lost
+//! positions become [`ChessEvaluation::Winner`] during search and [`best_move`] picks
+//! the DTZ-optimal move at the root. [`TablebaseConfig`] opens Syzygy tables as a
+//! [`SyzygyTablebase`], which decodes them with `shakmaty-syzygy`.
+
+use std::{
+    ffi::OsString,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use chess::{Board, BoardStatus, CastleRights, ChessMove, MoveGen, Piece, ALL_COLORS};
+use shakmaty::{fen::Fen, CastlingMode, Chess};
+
+use crate::{evaluator::ChessEvaluation, state::DrawReason};
+
+/// Win, draw or loss for the side to move. Cursed wins and blessed losses are decided
+/// by the fifty-move rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Wdl {
+    Loss,
+    BlessedLoss,
+    Draw,
+    CursedWin,
+    Win,
+}
+
+impl std::ops::Neg for Wdl {
+    type Output = Wdl;
+
+    fn neg(self) -> Wdl {
+        match self {
+            Wdl::Loss => Wdl::Win,
+            Wdl::BlessedLoss => Wdl::CursedWin,
+            Wdl::Draw => Wdl::Draw,
+            Wdl::CursedWin => Wdl::BlessedLoss,
+            Wdl::Win => Wdl::Loss,
+        }
+    }
+}
+
+/// Source of perfect endgame information.
+pub trait Tablebase: Send + Sync {
+    /// Largest number of pieces, kings included, that can be probed.
+    fn max_pieces(&self) -> u32;
+
+    /// Result for the side to move, `None` when the position is not in the tables.
+    fn probe_wdl(&self, board: &Board) -> Option<Wdl>;
+
+    /// Plies to the next capture or pawn move with best play, positive when the side to
+    /// move wins and negative when it loses, 0 for draws.
+    fn probe_dtz(&self, board: &Board) -> Option<i32>;
+}
+
+/// Whether `board` can be looked up in `tablebase`: few enough pieces and, as tables
+/// don't cover castling, no castling rights.
+pub fn probeable(tablebase: &dyn Tablebase, board: &Board) -> bool {
+    board.combined().popcnt() <= tablebase.max_pieces()
+        && ALL_COLORS
+            .iter()
+            .all(|&color| board.castle_rights(color) == CastleRights::NoRights)
+}
+
+/// Tablebase result of `board` as a search evaluation. Cursed wins and blessed losses
+/// are draws.
+pub fn evaluate(tablebase: &dyn Tablebase, board: &Board) -> Option<ChessEvaluation> {
+    if !probeable(tablebase, board) {
+        return None;
+    }
+
+    let side = board.side_to_move();
+    Some(match tablebase.probe_wdl(board)? {
+        Wdl::Win => ChessEvaluation::Winner(side),
+        Wdl::Loss => ChessEvaluation::Winner(!side),
+        _ => ChessEvaluation::Draw(DrawReason::Tablebase),
+    })
+}
+
+/// The move that wins fastest, or loses slowest, according to the DTZ tables, along with
+/// the result. `halfmove_clock` counts the plies since the last capture or pawn move;
+/// wins that take too long for the fifty-move rule rank as draws.
+pub fn best_move(
+    tablebase: &dyn Tablebase,
+    board: &Board,
+    halfmove_clock: u32,
+) -> Option<(ChessMove, Wdl)> {
+    if !probeable(tablebase, board) {
+        return None;
+    }
+
+    let mut best: Option<(i32, ChessMove, Wdl)> = None;
+    for mov in MoveGen::new_legal(board) {
+        let child = board.make_move_new(mov);
+        let zeroing = board.piece_on(mov.get_source()) == Some(Piece::Pawn)
+            || board.piece_on(mov.get_dest()).is_some();
+
+        // Signed plies to zeroing for us after playing `mov`.
+        let dtz = if child.status() == BoardStatus::Checkmate {
+            1
+        } else if zeroing {
+            match -tablebase.probe_wdl(&child)? {
+                Wdl::Win => 1,
+                Wdl::CursedWin => 101,
+                Wdl::Draw => 0,
+                Wdl::BlessedLoss => -101,
+                Wdl::Loss => -1,
+            }
+        } else {
+            match -tablebase.probe_dtz(&child)? {
+                dtz if dtz > 0 => dtz + 1,
+                dtz if dtz < 0 => dtz - 1,
+                _ => 0,
+            }
+        };
+
+        let in_time = dtz.unsigned_abs() + halfmove_clock <= 100;
+        let (rank, wdl) = match dtz {
+            d if d > 0 && in_time => (30_000 - d, Wdl::Win),
+            d if d > 0 => (20_000 - d, Wdl::CursedWin),
+            0 => (0, Wdl::Draw),
+            d if in_time => (-30_000 - d, Wdl::Loss),
+            d => (-20_000 - d, Wdl::BlessedLoss),
+        };
+
+        if best.is_none_or(|(best_rank, _, _)| rank > best_rank) {
+            best = Some((rank, mov, wdl));
+        }
+    }
+
+    best.map(|(_, mov, wdl)| (mov, wdl))
+}
+
+/// Syzygy WDL and DTZ tables. Positions are probed as if the last move was a capture or
+/// pawn move, the fifty-move rule only shows in cursed wins and blessed losses.
+pub struct SyzygyTablebase {
+    tables: shakmaty_syzygy::Tablebase<Chess>,
+    max_pieces: u32,
+}
+
+impl SyzygyTablebase {
+    /// Loads the tables in `directories`, probing at most `max_pieces` pieces.
+    pub fn open<P: AsRef<Path>>(
+        directories: impl IntoIterator<Item = P>,
+        max_pieces: u32,
+    ) -> io::Result<Self> {
+        let mut tables = shakmaty_syzygy::Tablebase::new();
+        for directory in directories {
+            tables.add_directory(directory)?;
+        }
+
+        let max_pieces = max_pieces.min(tables.max_pieces() as u32);
+        Ok(Self { tables, max_pieces })
+    }
+
+    fn position(board: &Board) -> Option<Chess> {
+        let fen = board.to_string().parse::<Fen>().ok()?;
+        fen.into_position(CastlingMode::Standard).ok()
+    }
+}
+
+impl Tablebase for SyzygyTablebase {
+    fn max_pieces(&self) -> u32 {
+        self.max_pieces
+    }
+
+    fn probe_wdl(&self, board: &Board) -> Option<Wdl> {
+        let position = Self::position(board)?;
+
+        Some(match self.tables.probe_wdl_after_zeroing(&position).ok()? {
+            shakmaty_syzygy::Wdl::Loss => Wdl::Loss,
+            shakmaty_syzygy::Wdl::BlessedLoss => Wdl::BlessedLoss,
+            shakmaty_syzygy::Wdl::Draw => Wdl::Draw,
+            shakmaty_syzygy::Wdl::CursedWin => Wdl::CursedWin,
+            shakmaty_syzygy::Wdl::Win => Wdl::Win,
+        })
+    }
+
+    fn probe_dtz(&self, board: &Board) -> Option<i32> {
+        let position = Self::position(board)?;
+        let dtz = self.tables.probe_dtz(&position).ok()?;
+
+        Some(dtz.ignore_rounding().0)
+    }
+}
+
+/// Where to look for Syzygy tables and how many pieces to probe at most.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TablebaseConfig {
+    /// Directories with `.rtbw` and `.rtbz` files, separated like `PATH`.
+    pub path: Option<OsString>,
+    pub max_pieces: u32,
+}
+
+impl Default for TablebaseConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            max_pieces: 6,
+        }
+    }
+}
+
+impl TablebaseConfig {
+    /// The Syzygy files in the configured directories.
+    pub fn table_files(&self) -> io::Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+
+        for directory in self.path.iter().flat_map(std::env::split_paths) {
+            for entry in fs::read_dir(&directory)? {
+                let path = entry?.path();
+                if is_table_file(&path) {
+                    files.push(path);
+                }
+            }
+        }
+
+        files.sort();
+        Ok(files)
+    }
+
+    /// Opens the configured tables. Without a path, with `max_pieces` below three or
+    /// with no tables in the directories this is `None` and the engine searches as
+    /// without tablebases.
+    pub fn open(&self) -> io::Result<Option<Arc<dyn Tablebase>>> {
+        if self.max_pieces < 3 {
+            return Ok(None);
+        }
+
+        if self.table_files()?.is_empty() {
+            return Ok(None);
+        }
+
+        let directories = self.path.iter().flat_map(std::env::split_paths);
+        let tablebase = SyzygyTablebase::open(directories, self.max_pieces)?;
+        Ok(Some(Arc::new(tablebase)))
+    }
+}
+
+fn is_table_file(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|extension| extension.to_str()),
+        Some("rtbw" | "rtbz")
+    )
+}
@@ -0,0 +1,135 @@
+//! How long to think when playing on a clock. Every move gets a soft limit, its share
+//! of the remaining time, and a hard limit it never exceeds. The search runs to the
+//! soft limit unless the best move keeps changing or its score drops, which stretch it
+//! towards the hard limit, or the best move has so many more visits that no other move
+//! can catch up before time runs out.
+
+use std::time::Duration;
+
+use chess::ChessMove;
+
+/// Time kept in reserve on the clock so lag does not flag the engine.
+pub const MOVE_OVERHEAD: Duration = Duration::from_millis(50);
+/// Moves the remaining time is spread over when the time control doesn't say.
+const DEFAULT_MOVES_TO_GO: u32 = 30;
+/// Hard limit as a multiple of the soft limit.
+const HARD_FACTOR: f64 = 3.0;
+/// Largest share of the remaining time a single move may take.
+const MAX_SHARE: f64 = 0.5;
+/// Part of the soft limit searched before the root is trusted to judge stability.
+const SETTLE_FRACTION: f64 = 0.25;
+/// Extension of the time to use whenever the best move changes.
+const INSTABILITY_FACTOR: f64 = 1.4;
+/// Drop of the best move's score in centipawns that calls for more time.
+const SCORE_DROP: f64 = 30.0;
+const SCORE_DROP_FACTOR: f64 = 2.0;
+
+/// Time left on the clock of the side to move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Clock {
+    pub remaining: Duration,
+    pub increment: Duration,
+    /// Moves until the next time control, when there is one.
+    pub movestogo: Option<u32>,
+}
+
+/// Decides when to stop the search of one move.
+#[derive(Debug, Clone)]
+pub struct TimeManager {
+    soft: Duration,
+    hard: Duration,
+    /// The soft limit stretched by the extensions so far, at most the hard limit.
+    target: Duration,
+    best_move: Option<ChessMove>,
+    /// Score of the best move once the search settled.
+    settled_score: Option<f64>,
+    score_dropped: bool,
+}
+
+impl TimeManager {
+    pub fn new(clock: &Clock) -> Self {
+        let available = clock.remaining.saturating_sub(MOVE_OVERHEAD);
+        let movestogo = clock.movestogo.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+
+        let soft = (clock.remaining / movestogo + clock.increment * 3 / 4).min(available);
+        let hard = soft
+            .mul_f64(HARD_FACTOR)
+            .min(available.mul_f64(MAX_SHARE))
+            .max(soft);
+
+        TimeManager {
+            soft,
+            hard,
+            target: soft,
+            best_move: None,
+            settled_score: None,
+            score_dropped: false,
+        }
+    }
+
+    pub fn soft_limit(&self) -> Duration {
+        self.soft
+    }
+
+    pub fn hard_limit(&self) -> Duration {
+        self.hard
+    }
+
+    /// Time the search currently means to use, between the soft and hard limit.
+    pub fn target(&self) -> Duration {
+        self.target
+    }
+
+    /// Checks the root after `elapsed` of searching, given the visits of every root move
+    /// and the score of the best one for the side to move. Returns whether to stop.
+    pub fn should_stop(
+        &mut self,
+        elapsed: Duration,
+        root_visits: &[(ChessMove, u64)],
+        score: Option<f64>,
+    ) -> bool {
+        if elapsed >= self.hard {
+            return true;
+        }
+
+        let mut visits = root_visits.to_vec();
+        visits.sort_by_key(|(_, visits)| std::cmp::Reverse(*visits));
+        let (best_move, best_visits) = match visits.first() {
+            Some(&(mov, visits)) if visits > 0 => (mov, visits),
+            _ => return false,
+        };
+        let second_visits = visits.get(1).map_or(0, |(_, visits)| *visits);
+
+        if self.best_move.is_some_and(|previous| previous != best_move) {
+            self.extend(INSTABILITY_FACTOR);
+        }
+        self.best_move = Some(best_move);
+
+        if elapsed < self.soft.mul_f64(SETTLE_FRACTION) {
+            return false;
+        }
+
+        if let Some(score) = score {
+            let settled = *self.settled_score.get_or_insert(score);
+            if !self.score_dropped && settled - score >= SCORE_DROP {
+                self.score_dropped = true;
+                self.extend(SCORE_DROP_FACTOR);
+            }
+        }
+
+        if elapsed >= self.target {
+            return true;
+        }
+
+        // At the rate so far, the runner-up can't overtake the best move in time.
+        let total = visits.iter().map(|(_, visits)| visits).sum::<u64>();
+        let rate = total as f64 / elapsed.as_secs_f64().max(f64::EPSILON);
+        let reachable = rate * (self.target - elapsed).as_secs_f64();
+
+        (best_visits - second_visits) as f64 > reachable
+    }
+
+    fn extend(&mut self, factor: f64) {
+        self.target = self.target.mul_f64(factor).min(self.hard);
+    }
+}
@@ -0,0 +1,274 @@
+use std::{collections::HashMap, hash::Hash, sync::Mutex};
+
+use mcts::{
+    tree_policy::{PolicyRng, TreePolicy},
+    Move, MoveInfo, Player, SearchHandle, MCTS,
+};
+
+/// PUCT as in AlphaGo: every move carries a prior probability from the evaluator, and
+/// moves the prior likes are explored first.
+#[derive(Debug, Clone, Copy)]
+pub struct PuctPolicy {
+    exploration_constant: f64,
+}
+
+impl PuctPolicy {
+    pub fn new(exploration_constant: f64) -> Self {
+        assert!(
+            exploration_constant > 0.0,
+            "exploration constant must be positive"
+        );
+
+        Self {
+            exploration_constant,
+        }
+    }
+
+    pub fn exploration_constant(&self) -> f64 {
+        self.exploration_constant
+    }
+}
+
+impl<Spec> TreePolicy<Spec> for PuctPolicy
+where
+    Spec: MCTS<TreePolicy = Self>,
+{
+    /// Prior probability of the move.
+    type MoveEvaluation = f64;
+    type ThreadLocalData = PolicyRng;
+
+    fn choose_child<'a, MoveIter>(
+        &self,
+        moves: MoveIter,
+        mut handle: SearchHandle<Spec>,
+    ) -> &'a MoveInfo<Spec>
+    where
+        MoveIter: Iterator<Item = &'a MoveInfo<Spec>> + Clone,
+    {
+        let total_visits = moves.clone().map(|x| x.visits()).sum::<u64>();
+        let explore_coef = self.exploration_constant * ((total_visits + 1) as f64).sqrt();
+
+        handle
+            .thread_data()
+            .policy_data
+            .select_by_key(moves, |mov| {
+                let prior = *mov.move_evaluation();
+                (mov.sum_rewards() + explore_coef * prior) / (mov.visits() + 1) as f64
+            })
+            .unwrap()
+    }
+
+    fn validate_evaluations(&self, evalns: &[f64]) {
+        for &prior in evalns {
+            assert!(prior >= 0.0, "move priors must not be negative");
+        }
+    }
+}
+
+/// All-moves-as-first statistics of a single move.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct AmafStats {
+    pub visits: u64,
+    pub sum_rewards: f64,
+}
+
+/// AMAF statistics of every move that was played from a node (or later in a playout
+/// by the same player), used as `NodeData` by specs running the [`RavePolicy`].
+pub struct AmafTable<M>(Mutex<HashMap<M, AmafStats>>);
+
+impl<M> Default for AmafTable<M> {
+    fn default() -> Self {
+        Self(Mutex::new(HashMap::new()))
+    }
+}
+
+impl<M: Hash + Eq> AmafTable<M> {
+    pub fn get(&self, mov: &M) -> AmafStats {
+        self.0.lock().unwrap().get(mov).copied().unwrap_or_default()
+    }
+
+    fn add(&self, mov: M, reward: f64) {
+        let mut table = self.0.lock().unwrap();
+        let stats = table.entry(mov).or_default();
+        stats.visits += 1;
+        stats.sum_rewards += reward;
+    }
+}
+
+/// Thread local record of the moves chosen during the current playout.
+///
+/// The `mcts` crate only calls back for the nodes below the root, so the root table
+/// is updated lazily at the start of the next playout on the same thread.
+pub struct RavePlayout<M, P> {
+    rng: PolicyRng,
+    moves: Vec<(M, P)>,
+    rewards: Vec<f64>,
+    /// `None` while selecting, otherwise the number of nodes that still need a backup.
+    remaining: Option<usize>,
+}
+
+impl<M, P> Default for RavePlayout<M, P> {
+    fn default() -> Self {
+        Self {
+            rng: PolicyRng::new(),
+            moves: Vec::new(),
+            rewards: Vec::new(),
+            remaining: Some(0),
+        }
+    }
+}
+
+impl<M: Hash + Eq + Clone, P: PartialEq> RavePlayout<M, P> {
+    /// Adds the moves played from `start` onwards by the player to move at `start`.
+    fn update(&self, table: &AmafTable<M>, start: usize) {
+        let mover = match self.moves.get(start) {
+            Some((_, mover)) => mover,
+            None => return,
+        };
+
+        for (index, (mov, player)) in self.moves.iter().enumerate().skip(start) {
+            let first_occurrence = !self.moves[start..index]
+                .iter()
+                .any(|(earlier, by)| earlier == mov && by == player);
+
+            if player == mover && first_occurrence {
+                table.add(mov.clone(), self.rewards[index]);
+            }
+        }
+    }
+}
+
+/// Rapid Action Value Estimation: UCT where the value of a move is blended with its
+/// all-moves-as-first value, trusting the latter less as the move gains visits.
+///
+/// `equivalence` is the number of visits at which both estimates are weighed equally.
+#[derive(Debug, Clone, Copy)]
+pub struct RavePolicy {
+    exploration_constant: f64,
+    equivalence: f64,
+}
+
+impl RavePolicy {
+    pub fn new(exploration_constant: f64, equivalence: f64) -> Self {
+        assert!(
+            exploration_constant > 0.0,
+            "exploration constant must be positive"
+        );
+        assert!(equivalence > 0.0, "equivalence parameter must be positive");
+
+        Self {
+            exploration_constant,
+            equivalence,
+        }
+    }
+
+    pub fn exploration_constant(&self) -> f64 {
+        self.exploration_constant
+    }
+
+    pub fn equivalence(&self) -> f64 {
+        self.equivalence
+    }
+
+    fn beta(&self, visits: u64) -> f64 {
+        (self.equivalence / (3.0 * visits as f64 + self.equivalence)).sqrt()
+    }
+}
+
+impl<Spec> TreePolicy<Spec> for RavePolicy
+where
+    Spec: MCTS<TreePolicy = Self, NodeData = AmafTable<Move<Spec>>>,
+    Move<Spec>: Hash + Eq,
+    Player<Spec>: Clone + PartialEq + Send,
+{
+    /// The player making the move; AMAF statistics are collected per player.
+    type MoveEvaluation = Player<Spec>;
+    type ThreadLocalData = RavePlayout<Move<Spec>, Player<Spec>>;
+
+    fn choose_child<'a, MoveIter>(
+        &self,
+        moves: MoveIter,
+        mut handle: SearchHandle<Spec>,
+    ) -> &'a MoveInfo<Spec>
+    where
+        MoveIter: Iterator<Item = &'a MoveInfo<Spec>> + Clone,
+    {
+        let table = handle.node().data();
+        let playout = &mut handle.thread_data().policy_data;
+
+        // Selection only resumes after a backup when a new playout starts at the root.
+        if playout.remaining.is_some() {
+            playout.update(table, 0);
+            playout.moves.clear();
+            playout.rewards.clear();
+            playout.remaining = None;
+        }
+
+        let total_visits = moves.clone().map(|x| x.visits()).sum::<u64>();
+        let ln_adjusted_total = ((total_visits + 1) as f64).ln();
+
+        let choice = playout
+            .rng
+            .select_by_key(moves, |mov| {
+                let amaf = table.get(mov.get_move());
+                let child_visits = mov.visits();
+
+                if child_visits == 0 && amaf.visits == 0 {
+                    return f64::INFINITY;
+                }
+
+                let explore_term = if child_visits == 0 {
+                    2.0 * ln_adjusted_total.sqrt()
+                } else {
+                    2.0 * (ln_adjusted_total / child_visits as f64).sqrt()
+                };
+
+                let value = match (child_visits, amaf.visits) {
+                    (0, _) => amaf.sum_rewards / amaf.visits as f64,
+                    (_, 0) => mov.sum_rewards() / child_visits as f64,
+                    _ => {
+                        let beta = self.beta(child_visits);
+                        (1.0 - beta) * mov.sum_rewards() / child_visits as f64
+                            + beta * amaf.sum_rewards / amaf.visits as f64
+                    }
+                };
+
+                self.exploration_constant * explore_term + value
+            })
+            .unwrap();
+
+        playout
+            .moves
+            .push((choice.get_move().clone(), choice.move_evaluation().clone()));
+
+        choice
+    }
+}
+
+/// Backs up the AMAF statistics of the node in `handle`; call this from
+/// [`MCTS::on_backpropagation`] with the reward of the playout for a given player.
+pub fn backpropagate_amaf<Spec, F>(mut handle: SearchHandle<Spec>, reward: F)
+where
+    Spec: MCTS<TreePolicy = RavePolicy, NodeData = AmafTable<Move<Spec>>>,
+    Move<Spec>: Hash + Eq,
+    Player<Spec>: Clone + PartialEq + Send,
+    F: Fn(&Player<Spec>) -> f64,
+{
+    let table = handle.node().data();
+    let playout = &mut handle.thread_data().policy_data;
+
+    let remaining = match playout.remaining {
+        Some(remaining) => remaining,
+        None => {
+            playout.rewards = playout.moves.iter().map(|(_, p)| reward(p)).collect();
+            playout.moves.len()
+        }
+    };
+
+    // The callbacks run from the leaf upwards; the node at `remaining - 1` was reached
+    // by the move with the same index, so its own moves start one further.
+    if let Some(index) = remaining.checked_sub(1) {
+        playout.update(table, index + 1);
+        playout.remaining = Some(index);
+    }
+}
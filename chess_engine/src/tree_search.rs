@@ -0,0 +1,94 @@
+use search_tree::{
+    mini_max::{mini_max_until, MiniMaxResult},
+    SearchGame,
+};
+
+#[cfg(feature = "nnue")]
+use crate::nnue::NnueEvaluator;
+use crate::{
+    evaluator::{ChessEvaluation, ChessEvaluator},
+    state::GameWrapper,
+};
+
+/// Chess as a [`SearchGame`], so the `search_tree` algorithms can play it.
+pub struct ChessGame;
+
+impl SearchGame for ChessGame {
+    type Node = GameWrapper;
+    type Evaluator = ChessEvaluator;
+}
+
+/// Chess as a [`SearchGame`] evaluated by an [`NnueEvaluator`].
+#[cfg(feature = "nnue")]
+pub struct ChessNnueGame;
+
+#[cfg(feature = "nnue")]
+impl SearchGame for ChessNnueGame {
+    type Node = GameWrapper;
+    type Evaluator = NnueEvaluator;
+}
+
+/// Runs a `depth` ply minimax search from `root` with `evaluator` on a pool of `cores`
+/// threads, see [`mini_max_move`].
+pub fn find_mini_max_move<F, G>(
+    root: &GameWrapper,
+    depth: usize,
+    cores: usize,
+    evaluator: &ChessEvaluator,
+    should_stop: F,
+    should_deepen: G,
+) -> Option<MiniMaxResult<ChessGame>>
+where
+    F: Fn() -> bool + Send + Sync,
+    G: Fn() -> bool + Send,
+{
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(cores)
+        .build()
+        .expect("Could not build thread pool");
+
+    pool.install(|| mini_max_move(root, depth, evaluator, should_stop, should_deepen))
+}
+
+/// Minimax move for `root` and its evaluation, searched at most `depth` plies deep. Shallower searches go
+/// first and a forced win ends the search, since minimax scores all mates alike and
+/// would otherwise keep postponing a mate it can always still give. Once the first ply
+/// is searched, a deeper search only starts while `should_deepen` holds, and
+/// `should_stop` can end the search early with the result of the deepest search that
+/// finished.
+pub fn mini_max_move<F, G>(
+    root: &GameWrapper,
+    depth: usize,
+    evaluator: &ChessEvaluator,
+    should_stop: F,
+    should_deepen: G,
+) -> Option<MiniMaxResult<ChessGame>>
+where
+    F: Fn() -> bool + Sync,
+    G: Fn() -> bool,
+{
+    let player = root.board().side_to_move();
+    let mut found = None;
+
+    for depth in 1..=depth {
+        if depth > 1 && !should_deepen() {
+            break;
+        }
+
+        // The first ply always finishes, so there is a move to play.
+        let result = match depth {
+            1 => mini_max_until::<ChessGame, _>(evaluator, root.clone(), depth, &|| false),
+            _ => mini_max_until::<ChessGame, _>(evaluator, root.clone(), depth, &should_stop),
+        };
+        let Some(result) = result else {
+            break;
+        };
+        let won = result.evaluation == ChessEvaluation::Winner(player);
+        found = Some(result);
+        if won {
+            break;
+        }
+    }
+
+    found
+}
@@ -0,0 +1,148 @@
+//! Texel tuning: fits [`EvalParams`] to game results by minimising the squared error
+//! between the results and a logistic function of the static evaluation.
+//!
+//! The static evaluation is used as is, so the training positions should be quiet.
+
+use std::{fs, io, path::Path, str::FromStr, sync::Arc};
+
+use chess::{Board, Color};
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+
+use crate::evaluator::{params::EvalParams, ChessEvaluator};
+
+/// A position labelled with the result of the game it was taken from.
+#[derive(Debug, Clone)]
+pub struct TrainingPosition {
+    pub board: Board,
+    /// 1.0 when white won, 0.5 for a draw and 0.0 when black won.
+    pub result: f64,
+}
+
+/// Parses an EPD or FEN line with the result either as an opcode (`c9 "1-0";`), in
+/// brackets (`[0.5]`) or as a trailing PGN result.
+pub fn parse_epd_line(line: &str) -> Option<TrainingPosition> {
+    let tokens = line.split_whitespace().collect::<Vec<_>>();
+    if tokens.len() < 4 {
+        return None;
+    }
+
+    let result = tokens[4..].iter().find_map(|token| {
+        match token.trim_matches(|c| c == '"' || c == ';' || c == '[' || c == ']') {
+            "1-0" | "1.0" => Some(1.0),
+            "1/2-1/2" | "0.5" => Some(0.5),
+            "0-1" | "0.0" => Some(0.0),
+            _ => None,
+        }
+    })?;
+
+    let board = Board::from_str(&format!("{} 0 1", tokens[..4].join(" "))).ok()?;
+
+    Some(TrainingPosition { board, result })
+}
+
+/// Loads every labelled position in `path`, skipping lines that cannot be parsed.
+pub fn load_epd(path: impl AsRef<Path>) -> io::Result<Vec<TrainingPosition>> {
+    let text = fs::read_to_string(path)?;
+
+    Ok(text.lines().filter_map(parse_epd_line).collect())
+}
+
+/// Expected score for white of an evaluation of `centipawns`.
+pub fn sigmoid(centipawns: f64, k: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-k * centipawns / 400.0))
+}
+
+pub fn mean_squared_error(positions: &[TrainingPosition], params: &EvalParams, k: f64) -> f64 {
+    evaluation_error(positions, &white_evaluator(params), k)
+}
+
+fn white_evaluator(params: &EvalParams) -> ChessEvaluator {
+    ChessEvaluator::with_params(Color::White, Arc::new(params.clone()))
+}
+
+/// Mean squared error of the evaluations of `evaluator`, which has to evaluate for white.
+fn evaluation_error(positions: &[TrainingPosition], evaluator: &ChessEvaluator, k: f64) -> f64 {
+    let error = positions
+        .par_iter()
+        .map(|position| {
+            let expected = sigmoid(evaluator.evaluate_board(&position.board), k);
+            (position.result - expected).powi(2)
+        })
+        .sum::<f64>();
+
+    error / positions.len().max(1) as f64
+}
+
+/// Scaling constant `K` that fits `params` best, searched on a shrinking grid.
+pub fn find_k(positions: &[TrainingPosition], params: &EvalParams) -> f64 {
+    let evaluator = white_evaluator(params);
+    let (mut low, mut high) = (0.0, 3.0);
+
+    for _ in 0..4 {
+        let step = (high - low) / 10.0;
+        let best = (0..=10)
+            .map(|i| low + step * i as f64)
+            .map(|k| (k, evaluation_error(positions, &evaluator, k)))
+            .min_by(|a, b| a.1.partial_cmp(&b.1).expect("Could not compare"))
+            .map(|(k, _)| k)
+            .unwrap();
+
+        low = (best - step).max(0.0);
+        high = best + step;
+    }
+
+    (low + high) / 2.0
+}
+
+/// Texel's local search: every parameter is moved up or down by its entry in `steps`
+/// (see [`EvalParams::steps`]) as long as that lowers the error, for at most
+/// `max_iterations` passes. Parameters with a step of zero are kept. `on_iteration` is
+/// called after every pass with the pass number and the error. One evaluator, and so one
+/// pawn table, is used for the whole run.
+pub fn tune<F>(
+    positions: &[TrainingPosition],
+    params: &EvalParams,
+    k: f64,
+    steps: &[f32],
+    max_iterations: usize,
+    mut on_iteration: F,
+) -> EvalParams
+where
+    F: FnMut(usize, f64, &EvalParams),
+{
+    let mut vector = params.to_vector();
+    assert_eq!(vector.len(), steps.len(), "one step per parameter");
+    let mut evaluator = white_evaluator(params);
+    let mut best_error = evaluation_error(positions, &evaluator, k);
+
+    for iteration in 1..=max_iterations {
+        let mut improved = false;
+
+        for (i, &step) in steps.iter().enumerate() {
+            if step == 0.0 {
+                continue;
+            }
+
+            for delta in [step, -step] {
+                vector[i] += delta;
+                evaluator.set_params(Arc::new(EvalParams::from_vector(&vector)));
+                let error = evaluation_error(positions, &evaluator, k);
+
+                if error < best_error {
+                    best_error = error;
+                    improved = true;
+                    break;
+                }
+                vector[i] -= delta;
+            }
+        }
+
+        on_iteration(iteration, best_error, &EvalParams::from_vector(&vector));
+
+        if !improved {
+            break;
+        }
+    }
+
+    EvalParams::from_vector(&vector)
+}
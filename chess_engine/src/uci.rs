@@ -0,0 +1,444 @@
+use std::{
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+
+use chess::{Board, ChessMove, Color, Game};
+
+#[cfg(feature = "nnue")]
+use crate::nnue::Network;
+use crate::{
+    book::OpeningBook,
+    evaluator::{
+        params::EvalParams,
+        rollout::{LeafEvaluation, DEFAULT_ROLLOUT_PLIES},
+        WinProbability, DEFAULT_WIN_PROBABILITY_K,
+    },
+    search::{SearchConfig, SearchInfo, SearchLimits},
+    session::SearchSession,
+    tablebase::{Tablebase, TablebaseConfig},
+    time_manager::Clock,
+    SearchAlgorithm, SearchPolicy, PUCT_EXPLORATION, UCT_EXPLORATION,
+};
+
+const ENGINE_NAME: &str = "cat_chess";
+const ENGINE_AUTHOR: &str = "BlockCat";
+
+/// Arguments of the UCI `go` command.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GoCommand {
+    pub wtime: Option<Duration>,
+    pub btime: Option<Duration>,
+    pub winc: Option<Duration>,
+    pub binc: Option<Duration>,
+    pub movestogo: Option<u32>,
+    pub movetime: Option<Duration>,
+    pub nodes: Option<u64>,
+    pub infinite: bool,
+}
+
+impl GoCommand {
+    pub fn parse(args: &[&str]) -> GoCommand {
+        let mut go = GoCommand::default();
+        let mut args = args.iter();
+
+        while let Some(&arg) = args.next() {
+            let mut value = || args.next().and_then(|v| v.parse::<u64>().ok());
+
+            match arg {
+                "wtime" => go.wtime = value().map(Duration::from_millis),
+                "btime" => go.btime = value().map(Duration::from_millis),
+                "winc" => go.winc = value().map(Duration::from_millis),
+                "binc" => go.binc = value().map(Duration::from_millis),
+                "movestogo" => go.movestogo = value().map(|v| v as u32),
+                "movetime" => go.movetime = value().map(Duration::from_millis),
+                "nodes" => go.nodes = value(),
+                "infinite" => go.infinite = true,
+                _ => {}
+            }
+        }
+
+        go
+    }
+
+    /// Search limits for `side`; a plain `go` searches until `stop`.
+    pub fn limits(&self, side: Color) -> SearchLimits {
+        if self.infinite {
+            return SearchLimits::default();
+        }
+
+        let (time, inc) = match side {
+            Color::White => (self.wtime, self.winc),
+            Color::Black => (self.btime, self.binc),
+        };
+
+        SearchLimits {
+            movetime: self.movetime,
+            playouts: self.nodes,
+            nodes: None,
+            clock: time.map(|remaining| Clock {
+                remaining,
+                increment: inc.unwrap_or_default(),
+                movestogo: self.movestogo,
+            }),
+        }
+    }
+}
+
+/// Parses the arguments of `position [startpos | fen <fen>] [moves <move>...]`.
+pub fn parse_position(args: &[&str]) -> Option<Game> {
+    let moves_at = args.iter().position(|&arg| arg == "moves");
+    let (setup, moves) = match moves_at {
+        Some(index) => (&args[..index], &args[index + 1..]),
+        None => (args, &[][..]),
+    };
+
+    let mut game = match setup.split_first() {
+        Some((&"startpos", _)) => Game::new(),
+        Some((&"fen", fen)) => Game::new_with_board(Board::from_str(&fen.join(" ")).ok()?),
+        _ => return None,
+    };
+
+    for mov in moves {
+        let mov = ChessMove::from_str(mov).ok()?;
+        if !game.current_position().legal(mov) {
+            return None;
+        }
+        game.make_move(mov);
+    }
+
+    Some(game)
+}
+
+pub fn format_info(info: &SearchInfo) -> String {
+    let millis = info.elapsed.as_millis().max(1);
+    let mut line = format!(
+        "info depth {} nodes {} time {} nps {}",
+        info.pv.len().max(1),
+        info.playouts,
+        millis,
+        info.playouts as u128 * 1000 / millis
+    );
+
+    if let Some(score) = info.score {
+        line.push_str(&format!(" score cp {}", score.round() as i64));
+    }
+
+    if !info.pv.is_empty() {
+        let pv = info
+            .pv
+            .iter()
+            .map(|m| m.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        line.push_str(&format!(" pv {}", pv));
+    }
+
+    line
+}
+
+struct RunningSearch {
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<()>,
+}
+
+impl RunningSearch {
+    fn finish(self) {
+        self.stop.store(true, Ordering::Relaxed);
+        self.handle.join().expect("search thread panicked");
+    }
+}
+
+/// A UCI session; the engine state survives between `go` commands, and so does the
+/// search tree while the position stays the same.
+pub struct UciEngine {
+    game: Game,
+    threads: usize,
+    policy: SearchPolicy,
+    /// Exploration constant set with the `Exploration` option, which a change of policy
+    /// keeps instead of the policy's default.
+    exploration: Option<f64>,
+    params: Arc<EvalParams>,
+    win_probability: WinProbability,
+    leaf_evaluation: LeafEvaluation,
+    rollout_plies: usize,
+    tablebase: Option<Arc<dyn Tablebase>>,
+    book: Option<Arc<OpeningBook>>,
+    #[cfg(feature = "nnue")]
+    network: Option<Arc<Network>>,
+    session: Arc<Mutex<SearchSession>>,
+    /// An option changed the evaluation, so the tree is dropped before the next search.
+    clear_tree: bool,
+    search: Option<RunningSearch>,
+}
+
+impl Default for UciEngine {
+    fn default() -> Self {
+        Self {
+            game: Game::new(),
+            threads: 4,
+            policy: SearchPolicy::default(),
+            exploration: None,
+            params: Arc::new(EvalParams::default()),
+            win_probability: WinProbability::default(),
+            leaf_evaluation: LeafEvaluation::default(),
+            rollout_plies: DEFAULT_ROLLOUT_PLIES,
+            tablebase: None,
+            book: None,
+            #[cfg(feature = "nnue")]
+            network: None,
+            session: Arc::new(Mutex::new(SearchSession::new(SearchConfig::default()))),
+            clear_tree: false,
+            search: None,
+        }
+    }
+}
+
+impl UciEngine {
+    /// Handles one line of input; returns `false` when the engine should quit.
+    pub fn handle_command(&mut self, line: &str) -> bool {
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+        let (command, args) = match tokens.split_first() {
+            Some((command, args)) => (*command, args),
+            None => return true,
+        };
+
+        match command {
+            "uci" => {
+                println!("id name {}", ENGINE_NAME);
+                println!("id author {}", ENGINE_AUTHOR);
+                println!("option name Threads type spin default 4 min 1 max 256");
+                println!("option name Policy type combo default UCT var UCT var RAVE var PUCT");
+                println!(
+                    "option name Exploration type string default {}",
+                    UCT_EXPLORATION
+                );
+                println!("option name RaveEquivalence type spin default 1000 min 1 max 1000000");
+                println!("option name EvalFile type string default <empty>");
+                println!(
+                    "option name WinProbabilityK type string default {}",
+                    DEFAULT_WIN_PROBABILITY_K
+                );
+                println!(
+                    "option name Rollout type combo default Static var Static var Random var Heavy var Hybrid"
+                );
+                println!(
+                    "option name RolloutPlies type spin default {} min 1 max 1000",
+                    DEFAULT_ROLLOUT_PLIES
+                );
+                println!("option name SyzygyPath type string default <empty>");
+                println!("option name BookFile type string default <empty>");
+                #[cfg(feature = "nnue")]
+                println!("option name NnueFile type string default <empty>");
+                println!("uciok");
+            }
+            "isready" => println!("readyok"),
+            "ucinewgame" => {
+                self.stop_search();
+                self.game = Game::new();
+                self.session.lock().unwrap().clear();
+            }
+            "setoption" => self.set_option(args),
+            "position" => {
+                self.stop_search();
+                match parse_position(args) {
+                    Some(game) => self.game = game,
+                    None => println!("info string invalid position: {}", args.join(" ")),
+                }
+            }
+            "go" => self.go(GoCommand::parse(args)),
+            "stop" => self.stop_search(),
+            "quit" => {
+                self.stop_search();
+                return false;
+            }
+            _ => println!("info string unknown command: {}", command),
+        }
+
+        true
+    }
+
+    fn set_option(&mut self, args: &[&str]) {
+        let name_at = args
+            .iter()
+            .position(|&arg| arg == "name")
+            .map_or(0, |index| index + 1);
+        let value_at = args.iter().position(|&arg| arg == "value");
+        let name = args[name_at..value_at.unwrap_or(args.len()).max(name_at)].join(" ");
+        let value = value_at
+            .map(|index| args[index + 1..].join(" "))
+            .unwrap_or_default();
+
+        // Apart from the thread count, every option changes the policy, which starts a
+        // new tree anyway, or how positions are evaluated.
+        if !name.eq_ignore_ascii_case("threads") {
+            self.clear_tree = true;
+        }
+
+        let equivalence = match self.policy {
+            SearchPolicy::Rave { equivalence, .. } => equivalence,
+            SearchPolicy::Uct { .. } | SearchPolicy::Puct { .. } => 1000.0,
+        };
+        let exploration = self.exploration.unwrap_or(UCT_EXPLORATION);
+
+        match (name.to_lowercase().as_str(), value.as_str()) {
+            ("threads", value) => match value.parse::<usize>() {
+                Ok(threads) if threads > 0 => self.threads = threads,
+                _ => println!("info string invalid thread count: {}", value),
+            },
+            ("policy", value) if value.eq_ignore_ascii_case("uct") => {
+                self.policy = SearchPolicy::Uct { exploration }
+            }
+            ("policy", value) if value.eq_ignore_ascii_case("rave") => {
+                self.policy = SearchPolicy::Rave {
+                    exploration,
+                    equivalence,
+                }
+            }
+            ("policy", value) if value.eq_ignore_ascii_case("puct") => {
+                self.policy = SearchPolicy::Puct {
+                    exploration: self.exploration.unwrap_or(PUCT_EXPLORATION),
+                }
+            }
+            ("exploration", value) => match value.parse::<f64>() {
+                Ok(exploration) if exploration > 0.0 => {
+                    self.exploration = Some(exploration);
+                    self.policy = self.policy.with_exploration(exploration)
+                }
+                _ => println!("info string invalid exploration constant: {}", value),
+            },
+            ("raveequivalence", value) => match value.parse::<f64>() {
+                Ok(equivalence) if equivalence > 0.0 => {
+                    if let SearchPolicy::Rave { exploration, .. } = self.policy {
+                        self.policy = SearchPolicy::Rave {
+                            exploration,
+                            equivalence,
+                        };
+                    }
+                }
+                _ => println!("info string invalid equivalence: {}", value),
+            },
+            ("evalfile", "" | "<empty>") => self.params = Arc::new(EvalParams::default()),
+            ("evalfile", path) => match EvalParams::load(path) {
+                Ok(params) => self.params = Arc::new(params),
+                Err(e) => println!("info string could not load {}: {}", path, e),
+            },
+            ("winprobabilityk", value) => match value.parse::<f64>() {
+                Ok(k) if k > 0.0 => self.win_probability = WinProbability::new(k),
+                _ => println!("info string invalid K: {}", value),
+            },
+            ("rollout", value) => match LeafEvaluation::named(value, self.rollout_plies) {
+                Some(leaf_evaluation) => self.leaf_evaluation = leaf_evaluation,
+                None => println!("info string unknown rollout: {}", value),
+            },
+            ("rolloutplies", value) => match value.parse::<usize>() {
+                Ok(plies) if plies > 0 => {
+                    self.rollout_plies = plies;
+                    self.leaf_evaluation = self.leaf_evaluation.with_plies(plies);
+                }
+                _ => println!("info string invalid rollout plies: {}", value),
+            },
+            ("syzygypath", "" | "<empty>") => self.tablebase = None,
+            ("syzygypath", path) => {
+                let config = TablebaseConfig {
+                    path: Some(path.into()),
+                    ..Default::default()
+                };
+                match config.open() {
+                    Ok(Some(tablebase)) => self.tablebase = Some(tablebase),
+                    Ok(None) => println!("info string no Syzygy tables in {}", path),
+                    Err(e) => println!("info string could not load {}: {}", path, e),
+                }
+            }
+            ("bookfile", "" | "<empty>") => self.book = None,
+            ("bookfile", path) => match OpeningBook::load(path) {
+                Ok(book) => self.book = Some(Arc::new(book)),
+                Err(e) => println!("info string could not load {}: {}", path, e),
+            },
+            #[cfg(feature = "nnue")]
+            ("nnuefile", "" | "<empty>") => self.network = None,
+            #[cfg(feature = "nnue")]
+            ("nnuefile", path) => match Network::load(path) {
+                Ok(network) => self.network = Some(Arc::new(network)),
+                Err(e) => println!("info string could not load {}: {}", path, e),
+            },
+            _ => println!("info string unknown option: {}", name),
+        }
+    }
+
+    fn go(&mut self, go: GoCommand) {
+        self.stop_search();
+
+        let game = self.game.clone();
+        let config = SearchConfig::default()
+            .with_algorithm(SearchAlgorithm::Mcts(self.policy))
+            .with_limits(go.limits(game.side_to_move()))
+            .with_threads(self.threads)
+            .with_params(self.params.clone())
+            .with_leaf_evaluation(self.leaf_evaluation)
+            .with_win_probability(self.win_probability);
+        let config = match &self.tablebase {
+            Some(tablebase) => config.with_tablebase(tablebase.clone()),
+            None => config,
+        };
+        let config = match &self.book {
+            Some(book) => config.with_book(book.clone()),
+            None => config,
+        };
+        #[cfg(feature = "nnue")]
+        let config = match &self.network {
+            Some(network) => config.with_network(network.clone()),
+            None => config,
+        };
+
+        let mut session = self.session.lock().unwrap();
+        if std::mem::take(&mut self.clear_tree) {
+            session.clear();
+        }
+        session.set_config(config);
+        drop(session);
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let (thread_stop, session) = (stop.clone(), self.session.clone());
+
+        let handle = std::thread::spawn(move || {
+            let mut session = session.lock().unwrap();
+            let best_move = session
+                .search(&game, &thread_stop, |info| {
+                    println!("{}", format_info(info))
+                })
+                .best_move;
+
+            match best_move {
+                Some(best_move) => println!("bestmove {}", best_move),
+                None => println!("bestmove 0000"),
+            }
+        });
+
+        self.search = Some(RunningSearch { stop, handle });
+    }
+
+    fn stop_search(&mut self) {
+        if let Some(search) = self.search.take() {
+            search.finish();
+        }
+    }
+}
+
+/// Runs the UCI loop on `lines` until `quit` or end of input.
+pub fn run<I: Iterator<Item = String>>(lines: I) {
+    let mut engine = UciEngine::default();
+
+    for line in lines {
+        if !engine.handle_command(&line) {
+            return;
+        }
+    }
+
+    engine.stop_search();
+}
@@ -0,0 +1,419 @@
+use std::{
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        Arc, Mutex,
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+
+use chess::{Board, BoardStatus, ChessMove, Color, Game};
+
+use crate::{
+    book::OpeningBook,
+    evaluator::params::EvalParams,
+    search::{SearchConfig, SearchInfo, SearchLimits},
+    session::SearchSession,
+    uci::GoCommand,
+    SearchAlgorithm, SearchPolicy,
+};
+
+const ENGINE_NAME: &str = "cat_chess";
+
+/// Time control set with `level` or `st`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TimeControl {
+    /// Conventional or incremental clock: moves per session (0 for all), increment.
+    Clock { moves: u32, increment: Duration },
+    /// Fixed time per move.
+    PerMove(Duration),
+}
+
+pub fn format_thinking(info: &SearchInfo) -> String {
+    let pv = info
+        .pv
+        .iter()
+        .map(|m| m.to_string())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    format!(
+        "{} {} {} {} {}",
+        info.pv.len().max(1),
+        info.score.unwrap_or(0.0).round() as i64,
+        info.elapsed.as_millis() / 10,
+        info.playouts,
+        pv
+    )
+}
+
+/// The result command for a game the board has ended, in the `RESULT {comment}` form.
+pub fn game_result(board: &Board) -> Option<&'static str> {
+    match (board.status(), board.side_to_move()) {
+        (BoardStatus::Checkmate, Color::White) => Some("0-1 {Black mates}"),
+        (BoardStatus::Checkmate, Color::Black) => Some("1-0 {White mates}"),
+        (BoardStatus::Stalemate, _) => Some("1/2-1/2 {Stalemate}"),
+        (BoardStatus::Ongoing, _) => None,
+    }
+}
+
+/// Parses a `level` base time, either in minutes or as `minutes:seconds`.
+fn parse_base_time(base: &str) -> Option<Duration> {
+    match base.split_once(':') {
+        Some((minutes, seconds)) => Some(Duration::from_secs(
+            minutes.parse::<u64>().ok()? * 60 + seconds.parse::<u64>().ok()?,
+        )),
+        None => Some(Duration::from_secs_f64(base.parse::<f64>().ok()? * 60.0)),
+    }
+}
+
+struct RunningSearch {
+    stop: Arc<AtomicBool>,
+    abort: Arc<AtomicBool>,
+    handle: JoinHandle<()>,
+}
+
+/// A CECP session. Unlike UCI the engine keeps the game itself: the search thread sends
+/// its move and plays it in the game as soon as it is found. Commands that don't touch
+/// the game are handled while the search runs, the others wait for it.
+pub struct XBoardEngine {
+    game: Arc<Mutex<Game>>,
+    force: bool,
+    engine_color: Color,
+    post: bool,
+    threads: usize,
+    policy: SearchPolicy,
+    params: Arc<EvalParams>,
+    book: Option<Arc<OpeningBook>>,
+    time_control: Option<TimeControl>,
+    engine_time: Option<Duration>,
+    opponent_time: Option<Duration>,
+    engine_moves: Arc<AtomicU32>,
+    session: Arc<Mutex<SearchSession>>,
+    /// The weights changed, so the tree is dropped before the next search.
+    clear_tree: bool,
+    search: Option<RunningSearch>,
+}
+
+impl Default for XBoardEngine {
+    fn default() -> Self {
+        Self {
+            game: Arc::new(Mutex::new(Game::new())),
+            force: false,
+            engine_color: Color::Black,
+            post: false,
+            threads: 4,
+            policy: SearchPolicy::default(),
+            params: Arc::new(EvalParams::default()),
+            book: None,
+            time_control: None,
+            engine_time: None,
+            opponent_time: None,
+            engine_moves: Arc::new(AtomicU32::new(0)),
+            session: Arc::new(Mutex::new(SearchSession::new(SearchConfig::default()))),
+            clear_tree: false,
+            search: None,
+        }
+    }
+}
+
+impl XBoardEngine {
+    /// Handles one line of input; returns `false` when the engine should quit.
+    pub fn handle_command(&mut self, line: &str) -> bool {
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+        let (command, args) = match tokens.split_first() {
+            Some((command, args)) => (*command, args),
+            None => return true,
+        };
+
+        match command {
+            "?" => self.finish_search(true),
+            "post" => self.post = true,
+            "nopost" => self.post = false,
+            "xboard" | "accepted" | "rejected" | "random" | "hard" | "easy" | "computer" | "sd"
+            | "name" | "rating" | "ics" | "result" => {}
+            "quit" => {
+                self.abort_search();
+                return false;
+            }
+            "new" | "force" | "setboard" => {
+                self.abort_search();
+                self.handle_game_command(command, args);
+            }
+            "usermove" | "go" | "playother" => {
+                self.finish_search(false);
+                self.handle_game_command(command, args);
+            }
+            _ => self.handle_game_command(command, args),
+        }
+
+        true
+    }
+
+    fn handle_game_command(&mut self, command: &str, args: &[&str]) {
+        match command {
+            "protover" => {
+                println!("feature option=\"EvalFile -file \"");
+                println!("feature option=\"BookFile -file \"");
+                println!(
+                    "feature myname=\"{}\" usermove=1 setboard=1 ping=1 sigint=0 sigterm=0 colors=0 analyze=0 done=1",
+                    ENGINE_NAME
+                );
+            }
+            "ping" => println!("pong {}", args.first().unwrap_or(&"")),
+            "new" => {
+                *self.game.lock().unwrap() = Game::new();
+                self.force = false;
+                self.engine_color = Color::Black;
+                self.time_control = None;
+                self.engine_moves.store(0, Ordering::Relaxed);
+                self.session.lock().unwrap().clear();
+            }
+            "force" => self.force = true,
+            "go" => {
+                self.force = false;
+                self.engine_color = self.game.lock().unwrap().side_to_move();
+                self.start_search();
+            }
+            "playother" => {
+                self.force = false;
+                self.engine_color = !self.game.lock().unwrap().side_to_move();
+            }
+            "setboard" => match Board::from_str(&args.join(" ")) {
+                Ok(board) => {
+                    *self.game.lock().unwrap() = Game::new_with_board(board);
+                    self.engine_moves.store(0, Ordering::Relaxed);
+                    self.session.lock().unwrap().clear();
+                }
+                Err(_) => println!("tellusererror Illegal position"),
+            },
+            "usermove" => self.user_move(args.first().copied().unwrap_or("")),
+            "level" => self.set_level(args),
+            "st" => {
+                if let Some(seconds) = args.first().and_then(|s| s.parse::<f64>().ok()) {
+                    self.time_control =
+                        Some(TimeControl::PerMove(Duration::from_secs_f64(seconds)));
+                }
+            }
+            "time" => self.engine_time = parse_centiseconds(args),
+            "otim" => self.opponent_time = parse_centiseconds(args),
+            "cores" => {
+                if let Some(cores) = args.first().and_then(|s| s.parse::<usize>().ok()) {
+                    self.threads = cores.max(1);
+                }
+            }
+            "option" => self.set_option(&args.join(" ")),
+            _ => println!("Error (unknown command): {}", command),
+        }
+    }
+
+    fn user_move(&mut self, text: &str) {
+        let mut game = self.game.lock().unwrap();
+        let position = game.current_position();
+        let mov = ChessMove::from_str(text)
+            .ok()
+            .filter(|&mov| position.legal(mov));
+
+        match mov {
+            Some(mov) => {
+                game.make_move(mov);
+                let engine_to_move = game.side_to_move() == self.engine_color;
+                drop(game);
+
+                if !self.force && engine_to_move {
+                    self.start_search();
+                }
+            }
+            None => println!("Illegal move: {}", text),
+        }
+    }
+
+    fn set_level(&mut self, args: &[&str]) {
+        if let [moves, base, increment, ..] = args {
+            let moves = moves.parse::<u32>().unwrap_or(0);
+            let increment = increment.parse::<f64>().unwrap_or(0.0);
+
+            self.time_control = Some(TimeControl::Clock {
+                moves,
+                increment: Duration::from_secs_f64(increment),
+            });
+            self.engine_time = parse_base_time(base);
+            self.opponent_time = self.engine_time;
+        }
+    }
+
+    fn set_option(&mut self, option: &str) {
+        let (name, value) = match option.split_once('=') {
+            Some(option) => option,
+            None => return,
+        };
+
+        if name == "EvalFile" {
+            match EvalParams::load(value) {
+                Ok(params) => {
+                    self.params = Arc::new(params);
+                    self.clear_tree = true;
+                }
+                Err(e) => println!("tellusererror Could not load {}: {}", value, e),
+            }
+            return;
+        }
+
+        if name == "BookFile" {
+            match OpeningBook::load(value) {
+                Ok(book) => self.book = Some(Arc::new(book)),
+                Err(e) => println!("tellusererror Could not load {}: {}", value, e),
+            }
+            return;
+        }
+
+        match (name, value.parse::<f64>()) {
+            ("Threads", Ok(threads)) if threads >= 1.0 => self.threads = threads as usize,
+            ("Exploration", Ok(exploration)) if exploration > 0.0 => {
+                self.policy = self.policy.with_exploration(exploration)
+            }
+            _ => println!("Error (unknown option): {}", option),
+        }
+    }
+
+    fn limits(&self) -> SearchLimits {
+        match self.time_control {
+            Some(TimeControl::PerMove(movetime)) => SearchLimits {
+                movetime: Some(movetime),
+                ..SearchLimits::default()
+            },
+            Some(TimeControl::Clock { moves, increment }) => {
+                let movestogo = match moves {
+                    0 => None,
+                    moves => Some(moves - self.engine_moves.load(Ordering::Relaxed) % moves),
+                };
+                let (own, other) = (self.engine_time, self.opponent_time);
+                let (wtime, btime) = match self.engine_color {
+                    Color::White => (own, other),
+                    Color::Black => (other, own),
+                };
+
+                GoCommand {
+                    wtime,
+                    btime,
+                    winc: Some(increment),
+                    binc: Some(increment),
+                    movestogo,
+                    ..GoCommand::default()
+                }
+                .limits(self.engine_color)
+            }
+            None => SearchLimits {
+                movetime: Some(Duration::from_secs(5)),
+                ..SearchLimits::default()
+            },
+        }
+    }
+
+    fn start_search(&mut self) {
+        let game = self.game.lock().unwrap().clone();
+        let config = SearchConfig::default()
+            .with_algorithm(SearchAlgorithm::Mcts(self.policy))
+            .with_limits(self.limits())
+            .with_threads(self.threads)
+            .with_params(self.params.clone());
+        let config = match &self.book {
+            Some(book) => config.with_book(book.clone()),
+            None => config,
+        };
+
+        let mut session = self.session.lock().unwrap();
+        if std::mem::take(&mut self.clear_tree) {
+            session.clear();
+        }
+        session.set_config(config);
+        drop(session);
+
+        let post = self.post;
+        let stop = Arc::new(AtomicBool::new(false));
+        let abort = Arc::new(AtomicBool::new(false));
+        let (thread_stop, thread_abort) = (stop.clone(), abort.clone());
+        let (played, engine_moves) = (self.game.clone(), self.engine_moves.clone());
+        let session = self.session.clone();
+
+        let handle = std::thread::spawn(move || {
+            let best_move = session
+                .lock()
+                .unwrap()
+                .search(&game, &thread_stop, |info| {
+                    if post {
+                        println!("{}", format_thinking(info));
+                    }
+                })
+                .best_move;
+
+            // The game is locked while the move is sent, so the next command sees it.
+            let mut played = played.lock().unwrap();
+            if thread_abort.load(Ordering::Relaxed) {
+                return;
+            }
+
+            match best_move {
+                Some(mov) => {
+                    println!("move {}", mov);
+                    played.make_move(mov);
+                    engine_moves.fetch_add(1, Ordering::Relaxed);
+                }
+                None => match game_result(&played.current_position()) {
+                    Some(result) => println!("{}", result),
+                    None => println!("resign"),
+                },
+            }
+        });
+
+        self.search = Some(RunningSearch {
+            stop,
+            abort,
+            handle,
+        });
+    }
+
+    /// Waits for the running search to play its move, or stops it first when `move_now`
+    /// is set.
+    fn finish_search(&mut self, move_now: bool) {
+        if let Some(search) = self.search.take() {
+            if move_now {
+                search.stop.store(true, Ordering::Relaxed);
+            }
+            self.join_search(search);
+        }
+    }
+
+    /// Stops the running search without letting it move.
+    fn abort_search(&mut self) {
+        if let Some(search) = self.search.take() {
+            search.abort.store(true, Ordering::Relaxed);
+            search.stop.store(true, Ordering::Relaxed);
+            self.join_search(search);
+        }
+    }
+
+    fn join_search(&self, search: RunningSearch) {
+        search.handle.join().expect("search thread panicked");
+    }
+}
+
+fn parse_centiseconds(args: &[&str]) -> Option<Duration> {
+    args.first()
+        .and_then(|s| s.parse::<u64>().ok())
+        .map(|cs| Duration::from_millis(cs * 10))
+}
+
+/// Runs the CECP loop on `lines` until `quit` or end of input.
+pub fn run<I: Iterator<Item = String>>(lines: I) {
+    let mut engine = XBoardEngine::default();
+
+    for line in lines {
+        if !engine.handle_command(&line) {
+            return;
+        }
+    }
+
+    engine.abort_search();
+}
@@ -0,0 +1,148 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    process::{Command, Stdio},
+    str::FromStr,
+    sync::Arc,
+};
+
+use chess::{Board, ChessMove, Game};
+use chess_engine::{
+    book::{polyglot_hash, OpeningBook},
+    search::{MoveSource, SearchConfig},
+};
+use rand::{rngs::StdRng, SeedableRng};
+
+fn play(moves: &[&str]) -> Board {
+    moves.iter().fold(Board::default(), |board, mov| {
+        board.make_move_new(ChessMove::from_str(mov).unwrap())
+    })
+}
+
+/// Polyglot move encoding: destination in the low six bits, then the source square.
+fn encode(mov: &str, promotion: u16) -> u16 {
+    let square = |s: &[u8]| (s[0] - b'a') as u16 | ((s[1] - b'1') as u16) << 3;
+    let bytes = mov.as_bytes();
+
+    square(&bytes[2..4]) | square(&bytes[0..2]) << 6 | promotion << 12
+}
+
+fn entry(board: &Board, mov: &str, weight: u16) -> Vec<u8> {
+    let mut bytes = polyglot_hash(board).to_be_bytes().to_vec();
+    bytes.extend(encode(mov, 0).to_be_bytes());
+    bytes.extend(weight.to_be_bytes());
+    bytes.extend(0u32.to_be_bytes());
+    bytes
+}
+
+#[test]
+fn test_polyglot_hash() {
+    // The reference keys from the Polyglot book format description.
+    let cases: [(&[&str], u64); 9] = [
+        (&[], 0x463b96181691fc9c),
+        (&["e2e4"], 0x823c9b50fd114196),
+        (&["e2e4", "d7d5"], 0x0756b94461c50fb0),
+        (&["e2e4", "d7d5", "e4e5"], 0x662fafb965db29d4),
+        (&["e2e4", "d7d5", "e4e5", "f7f5"], 0x22a48b5a8e47ff78),
+        (
+            &["e2e4", "d7d5", "e4e5", "f7f5", "e1e2"],
+            0x652a607ca3f242c1,
+        ),
+        (
+            &["e2e4", "d7d5", "e4e5", "f7f5", "e1e2", "e8f7"],
+            0x00fdd303c946bdd9,
+        ),
+        (
+            &["a2a4", "b7b5", "h2h4", "b5b4", "c2c4"],
+            0x3c8123ea7b067637,
+        ),
+        (
+            &["a2a4", "b7b5", "h2h4", "b5b4", "c2c4", "b4c3", "a1a3"],
+            0x5c3f9b829b279560,
+        ),
+    ];
+
+    for (moves, key) in cases {
+        assert_eq!(key, polyglot_hash(&play(moves)), "{:?}", moves);
+    }
+}
+
+#[test]
+fn test_book_moves() {
+    let start = Board::default();
+    let italian = play(&["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "f8c5"]);
+
+    let bytes = [
+        entry(&start, "e2e4", 3),
+        entry(&start, "d2d4", 1),
+        entry(&start, "e2e5", 100),
+        entry(&start, "g1f3", 0),
+        // Castling short, written as the king taking its rook.
+        entry(&italian, "e1h1", 5),
+    ]
+    .concat();
+
+    let book = OpeningBook::from_bytes(&bytes).unwrap();
+    assert_eq!(5, book.len());
+    assert_eq!(4, book.entries(&start).len());
+
+    let e4 = ChessMove::from_str("e2e4").unwrap();
+    let d4 = ChessMove::from_str("d2d4").unwrap();
+    let nf3 = ChessMove::from_str("g1f3").unwrap();
+    // The illegal move is dropped.
+    assert_eq!(vec![(e4, 3), (d4, 1), (nf3, 0)], book.moves(&start));
+
+    let castle = ChessMove::from_str("e1g1").unwrap();
+    assert_eq!(vec![(castle, 5)], book.moves(&italian));
+    assert!(book.moves(&play(&["a2a3"])).is_empty());
+
+    let deterministic = book.clone().deterministic(true);
+    assert_eq!(Some((e4, 3)), deterministic.probe(&start));
+
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut counts = [0; 2];
+    for _ in 0..400 {
+        match book.probe_with_rng(&start, &mut rng) {
+            Some((mov, _)) if mov == e4 => counts[0] += 1,
+            Some((mov, _)) if mov == d4 => counts[1] += 1,
+            other => panic!("unexpected book move {:?}", other),
+        }
+    }
+    assert!(counts[0] > 2 * counts[1], "{:?}", counts);
+    assert!(counts[1] > 0);
+
+    // find_move plays the book move without searching.
+    let config = SearchConfig::default().with_book(Arc::new(deterministic));
+    let report = chess_engine::find_move(&Game::new(), &config);
+    assert_eq!(Some(e4), report.best_move);
+    assert_eq!(MoveSource::Book, report.source);
+
+    assert!(OpeningBook::from_bytes(&bytes[..20]).is_err());
+}
+
+#[test]
+fn test_uci_book_move() {
+    let path = std::env::temp_dir().join("chess_engine_uci_book.bin");
+    std::fs::write(&path, entry(&Board::default(), "b1c3", 1)).unwrap();
+
+    let mut engine = Command::new(env!("CARGO_BIN_EXE_uci"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut input = engine.stdin.take().unwrap();
+    writeln!(input, "uci").unwrap();
+    writeln!(input, "setoption name BookFile value {}", path.display()).unwrap();
+    writeln!(input, "position startpos").unwrap();
+    writeln!(input, "go movetime 60000").unwrap();
+
+    // The book move comes right away, long before the movetime is up.
+    let output = BufReader::new(engine.stdout.take().unwrap());
+    let bestmove = output
+        .lines()
+        .map_while(Result::ok)
+        .find(|line| line.starts_with("bestmove"));
+    writeln!(input, "quit").unwrap();
+    engine.wait().unwrap();
+
+    assert_eq!(Some("bestmove b1c3"), bestmove.as_deref());
+}
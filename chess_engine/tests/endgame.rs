@@ -0,0 +1,108 @@
+use std::{str::FromStr, sync::Arc};
+
+use chess::{Board, Color, Game};
+use chess_engine::{
+    evaluator::{
+        endgame::{self, MatingNet},
+        params::EvalParams,
+        ChessEvaluation, ChessEvaluator,
+    },
+    state::GameWrapper,
+    tree_search,
+};
+use mcts::GameState;
+
+/// Moves the winning side gets to deliver mate, as the fifty-move rule allows.
+const MOVE_LIMIT: usize = 50;
+
+/// Lets the engine play both sides of `fen` for up to `MOVE_LIMIT` moves each. The
+/// wrapper is kept across moves so the search sees the repetitions of the game so far.
+fn play_out(fen: &str) -> Option<ChessEvaluation> {
+    let mut state = GameWrapper::new(Game::from_str(fen).unwrap());
+    let params = Arc::new(EvalParams::default());
+
+    for _ in 0..2 * MOVE_LIMIT {
+        if state.outcome().is_some() {
+            break;
+        }
+
+        let color = state.board().side_to_move();
+        let evaluator = ChessEvaluator::with_params(color, params.clone());
+        let mov = tree_search::mini_max_move(&state, 4, &evaluator, || false, || true)
+            .unwrap()
+            .found_move;
+        state.make_move(&mov);
+    }
+
+    state.outcome()
+}
+
+#[test]
+fn test_mating_net() {
+    let net = |fen: &str, strong| endgame::mating_net(strong, &Board::from_str(fen).unwrap());
+
+    assert_eq!(
+        Some(MatingNet::Edge),
+        net("8/8/8/4k3/8/8/8/3QK3 w - - 0 1", Color::White)
+    );
+    assert_eq!(None, net("8/8/8/4k3/8/8/8/3QK3 w - - 0 1", Color::Black));
+    assert_eq!(
+        Some(MatingNet::Edge),
+        net("8/8/8/4k3/8/8/8/2BBK3 w - - 0 1", Color::White)
+    );
+    assert_eq!(None, net("8/8/8/4k3/8/8/8/1B1BK3 w - - 0 1", Color::White));
+    assert_eq!(
+        Some(MatingNet::LightCorner),
+        net("8/8/8/4k3/8/8/8/2NBK3 w - - 0 1", Color::White)
+    );
+    assert_eq!(
+        Some(MatingNet::Edge),
+        net("8/8/8/4k3/8/3r4/8/3QK3 w - - 0 1", Color::White)
+    );
+    assert_eq!(None, net("8/8/8/4k3/8/3n4/8/3RK3 w - - 0 1", Color::White));
+    assert_eq!(None, net("8/8/8/4k3/8/8/8/2NNK3 w - - 0 1", Color::White));
+}
+
+#[test]
+fn test_mop_up_drives_king_to_edge() {
+    let weights = EvalParams::default().weights;
+    let mop_up =
+        |fen: &str, turn| endgame::evaluate(turn, &Board::from_str(fen).unwrap(), &weights);
+
+    // Enemy king on the edge and kings in opposition against a centralised enemy king.
+    let edge = mop_up("3k4/8/3K4/8/8/8/8/7R w - - 0 1", Color::White);
+    let center = mop_up("8/8/8/3k4/8/8/8/4K2R w - - 0 1", Color::White);
+    assert_eq!(3.0 * 10.0 + 12.0 * 15.0, edge);
+    assert_eq!(0.0 * 10.0 + 9.0 * 15.0, center);
+    assert_eq!(
+        -edge,
+        mop_up("3k4/8/3K4/8/8/8/8/7R w - - 0 1", Color::Black)
+    );
+
+    // A knight can't mate.
+    assert_eq!(0.0, mop_up("3k4/8/3K4/8/8/8/8/7N w - - 0 1", Color::White));
+}
+
+#[test]
+fn test_mates_kqk() {
+    assert_eq!(
+        Some(ChessEvaluation::Winner(Color::White)),
+        play_out("8/8/8/4k3/8/8/8/3QK3 w - - 0 1")
+    );
+    assert_eq!(
+        Some(ChessEvaluation::Winner(Color::Black)),
+        play_out("3qk3/8/8/8/4K3/8/8/8 b - - 0 1")
+    );
+}
+
+#[test]
+fn test_mates_krk() {
+    assert_eq!(
+        Some(ChessEvaluation::Winner(Color::White)),
+        play_out("8/8/8/4k3/8/8/8/R3K3 w - - 0 1")
+    );
+    assert_eq!(
+        Some(ChessEvaluation::Winner(Color::Black)),
+        play_out("4k2r/8/8/8/3K4/8/8/8 b - - 0 1")
+    );
+}
@@ -0,0 +1,114 @@
+use std::{str::FromStr, time::Duration};
+
+use chess::ChessMove;
+use chess_engine::{
+    epd::{self, SuiteSummary},
+    search::SearchConfig,
+    SearchAlgorithm,
+};
+
+#[test]
+fn test_parse_epd_position() {
+    let position = epd::parse_epd_position(
+        "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id \"WAC.001\";",
+    )
+    .unwrap();
+
+    assert_eq!(Some("WAC.001".to_string()), position.id);
+    assert_eq!(
+        vec![ChessMove::from_str("g3g6").unwrap()],
+        position.best_moves
+    );
+    assert!(position.avoid_moves.is_empty());
+
+    let position =
+        epd::parse_epd_position("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - am Ra7 Ra6+; bm Ra8#;").unwrap();
+    assert_eq!(
+        vec![ChessMove::from_str("a1a8").unwrap()],
+        position.best_moves
+    );
+    assert_eq!(2, position.avoid_moves.len());
+    assert!(position.is_solved(ChessMove::from_str("a1a8").unwrap()));
+    assert!(!position.is_solved(ChessMove::from_str("a1a7").unwrap()));
+
+    // Nothing to solve, or a move that is not legal.
+    assert!(epd::parse_epd_position("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - id \"x\";").is_none());
+    assert!(epd::parse_epd_position("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - bm Qa8;").is_none());
+}
+
+#[test]
+fn test_avoid_moves_only() {
+    let position = epd::parse_epd_position("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - am Ra7;").unwrap();
+
+    assert!(position.is_solved(ChessMove::from_str("a1a8").unwrap()));
+    assert!(!position.is_solved(ChessMove::from_str("a1a7").unwrap()));
+}
+
+#[test]
+fn test_load_suite_reports_skipped_lines() {
+    let path = std::env::temp_dir().join("chess_engine_suite.epd");
+    std::fs::write(
+        &path,
+        "6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - bm Ra8#;\n\
+         \n\
+         6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - bm Qa8;\n\
+         r5k1/5ppp/8/8/8/8/5PPP/6K1 b - - bm Ra1#;\n",
+    )
+    .unwrap();
+
+    let mut skipped = Vec::new();
+    let suite = epd::load_epd_suite(&path, |number, line| {
+        skipped.push((number, line.to_string()))
+    })
+    .unwrap();
+
+    assert_eq!(2, suite.len());
+    assert_eq!(
+        vec![(3, "6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - bm Qa8;".to_string())],
+        skipped
+    );
+}
+
+#[test]
+fn test_run_suite_minimax() {
+    let suite = [
+        "6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - bm Ra8#; id \"back rank\";",
+        "r5k1/5ppp/8/8/8/8/5PPP/6K1 b - - bm Ra1#; id \"back rank black\";",
+    ]
+    .iter()
+    .map(|line| epd::parse_epd_position(line).unwrap())
+    .collect::<Vec<_>>();
+
+    let mut reported = 0;
+    let config = SearchConfig::default().with_algorithm(SearchAlgorithm::MiniMax { depth: 2 });
+    let summary = epd::run_suite(&suite, &config, |_| reported += 1);
+
+    assert_eq!(2, reported);
+    assert_eq!(2, summary.total);
+    assert_eq!(2, summary.solved);
+    assert_eq!(Some("a1a8".to_string()), summary.positions[0].found);
+    assert_eq!(Some("back rank".to_string()), summary.positions[0].id);
+
+    let json = serde_json::to_string(&summary).unwrap();
+    assert_eq!(
+        summary,
+        serde_json::from_str::<SuiteSummary>(&json).unwrap()
+    );
+}
+
+#[test]
+fn test_mini_max_movetime() {
+    let suite = [epd::parse_epd_position(
+        "rnb1kbnr/pppp1ppp/8/4p3/4P2q/5N2/PPPP1PPP/RNBQKB1R w KQkq - bm Nxh4;",
+    )
+    .unwrap()];
+
+    // Far too deep to finish, the movetime ends the search with a shallower result.
+    let config = SearchConfig::default()
+        .with_algorithm(SearchAlgorithm::MiniMax { depth: 12 })
+        .with_movetime(Duration::from_millis(200));
+    let summary = epd::run_suite(&suite, &config, |_| {});
+
+    assert_eq!(1, summary.solved);
+    assert!(summary.millis < 5_000, "took {} ms", summary.millis);
+}
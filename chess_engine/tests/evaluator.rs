@@ -1,46 +1,223 @@
 use std::str::FromStr;
 
-use chess::{Color, Game, MoveGen};
+use chess::{Board, ChessMove, Color, Game, MoveGen};
 use chess_engine::{
-    evaluator::{self, ChessEvaluation, ChessEvaluator},
-    state::GameWrapper,
+    evaluator::{
+        king_safety, mobility,
+        pawns::{self, PawnTable, PawnWeights},
+        ChessEvaluation, ChessEvaluator, EvalWeights, WinProbability,
+    },
+    mcts_handler::{ChessMCTS, ChessRaveMCTS},
+    search::SearchConfig,
+    state::{DrawReason, GameWrapper},
+    tree_policy::RavePolicy,
+    UCT_EXPLORATION,
 };
-use mcts::Evaluator;
+use mcts::{transposition_table::ApproxTable, Evaluator, MCTSManager};
 
 #[test]
 fn test_mcts() {
-    let game = Game::from_str("rnb1kbnr/pppp1ppp/8/4p2Q/4P2q/8/PPPP1PPP/RNB1KBNR w KQkq - 2 3").unwrap();
+    let game =
+        Game::from_str("rnb1kbnr/pppp1ppp/8/4p2Q/4P2q/8/PPPP1PPP/RNB1KBNR w KQkq - 2 3").unwrap();
 
-    chess_engine::find_move(&game, 10_000, 16, Color::White);
-    
+    let config = SearchConfig::default()
+        .with_playouts(10_000)
+        .with_threads(16);
+    chess_engine::find_move(&game, &config);
 }
 
 #[test]
-fn test_eval_b() {
+fn test_mcts_rave() {
+    // Both queens hang, white to move takes first.
+    let game =
+        Game::from_str("rnb1kbnr/pppp1ppp/8/4p2Q/4P2q/8/PPPP1PPP/RNB1KBNR w KQkq - 2 3").unwrap();
+    let color = game.side_to_move();
+
+    let mut manager = MCTSManager::new(
+        GameWrapper::new(game),
+        ChessRaveMCTS(color, WinProbability::default()),
+        ChessEvaluator::new(color),
+        RavePolicy::new(UCT_EXPLORATION, 1000.0),
+        ApproxTable::new(1 << 16),
+    );
+    manager.playout_n(2_000);
+
+    // White's moves deeper in a playout count for the root moves as well, so the root
+    // gathers more AMAF updates than visits.
+    let root = manager.tree().root_node();
+    let (visits, amaf) = root.moves().fold((0, 0), |(visits, amaf), info| {
+        let stats = root.data().get(info.get_move());
+        (visits + info.visits(), amaf + stats.visits)
+    });
+    assert!(amaf > visits, "{} AMAF updates for {} visits", amaf, visits);
+
+    assert_eq!(
+        Some(ChessMove::from_str("h5h4").unwrap()),
+        manager.best_move()
+    );
+}
+
+/// Value of the position below for black with the default weights: 1031.25 of material
+/// and tapered piece-square tables (phase 13/24), -24 of pawn structure (black has one
+/// more isolated pawn and a doubled pawn), 51 of mobility and 6.5 of king safety (the
+/// bishop on c1 eyes the white king zone).
+const EVAL_B: f64 = 1064.75;
+
+fn evaluate(color: Color) -> (ChessEvaluator, ChessEvaluation) {
     let game = Game::from_str("rnb1k1nr/p4ppp/p1p5/8/8/2P2P1P/P5P1/1Rb1K1NR b kq - 1 14").unwrap();
 
     let moves = MoveGen::new_legal(&game.current_position()).collect::<Vec<_>>();
-    let evaluator = ChessEvaluator(Color::Black);
-    let eval = evaluator
-        .evaluate_new_state(&GameWrapper(game), &moves, None)
-        .1;
-    assert_eq!(ChessEvaluation::Evaluation(10.0), eval);
+    let evaluator = ChessEvaluator::new(color);
+    let eval = Evaluator::<ChessMCTS>::evaluate_new_state(
+        &evaluator,
+        &GameWrapper::new(game),
+        &moves,
+        None,
+    )
+    .1;
+
+    (evaluator, eval)
+}
 
-    assert_eq!(-10.0, evaluator.interpret_evaluation_for_player(&eval, &Color::White));
-    assert_eq!(10.0, evaluator.interpret_evaluation_for_player(&eval, &Color::Black));
+fn assert_evaluation(expected: f64, eval: &ChessEvaluation) {
+    match eval {
+        ChessEvaluation::Evaluation(e) => {
+            assert!((e - expected).abs() < 0.01, "{} != {}", e, expected)
+        }
+        _ => panic!("expected an evaluation, got {:?}", eval),
+    }
+}
+
+/// The rewards of both players for a position worth `EVAL_B` for black.
+fn assert_win_probabilities(white: f64, black: f64) {
+    let curve = WinProbability::default();
+
+    assert!((white + black - 1.0).abs() < 1e-9);
+    assert!((curve.centipawns(white) + EVAL_B).abs() < 0.01);
+    assert!((curve.centipawns(black) - EVAL_B).abs() < 0.01);
+}
+
+#[test]
+fn test_eval_b() {
+    let (evaluator, eval) = evaluate(Color::Black);
+    assert_evaluation(EVAL_B, &eval);
+
+    let white =
+        Evaluator::<ChessMCTS>::interpret_evaluation_for_player(&evaluator, &eval, &Color::White);
+    let black =
+        Evaluator::<ChessMCTS>::interpret_evaluation_for_player(&evaluator, &eval, &Color::Black);
+    assert_win_probabilities(white, black);
 }
 
 #[test]
 fn test_eval_w() {
-    let game = Game::from_str("rnb1k1nr/p4ppp/p1p5/8/8/2P2P1P/P5P1/1Rb1K1NR b kq - 1 14").unwrap();
+    let (evaluator, eval) = evaluate(Color::White);
+    assert_evaluation(-EVAL_B, &eval);
 
-    let moves = MoveGen::new_legal(&game.current_position()).collect::<Vec<_>>();
-    let evaluator = ChessEvaluator(Color::White);
-    let eval = evaluator
-        .evaluate_new_state(&GameWrapper(game), &moves, None)
-        .1;
-
-    assert_eq!(ChessEvaluation::Evaluation(-10.0), eval);
-    assert_eq!(-10.0, evaluator.interpret_evaluation_for_player(&eval, &Color::White));
-    assert_eq!(10.0, evaluator.interpret_evaluation_for_player(&eval, &Color::Black));
+    let white =
+        Evaluator::<ChessMCTS>::interpret_evaluation_for_player(&evaluator, &eval, &Color::White);
+    let black =
+        Evaluator::<ChessMCTS>::interpret_evaluation_for_player(&evaluator, &eval, &Color::Black);
+    assert_win_probabilities(white, black);
+}
+
+#[test]
+fn test_win_probability() {
+    let evaluator = ChessEvaluator::new(Color::White);
+    let reward = |evaluation: ChessEvaluation, player: Color| {
+        Evaluator::<ChessMCTS>::interpret_evaluation_for_player(&evaluator, &evaluation, &player)
+    };
+
+    assert_eq!(
+        1.0,
+        reward(ChessEvaluation::Winner(Color::White), Color::White)
+    );
+    assert_eq!(
+        0.0,
+        reward(ChessEvaluation::Winner(Color::White), Color::Black)
+    );
+    assert_eq!(
+        0.5,
+        reward(ChessEvaluation::Draw(DrawReason::Stalemate), Color::Black)
+    );
+    assert_eq!(0.5, reward(ChessEvaluation::Evaluation(0.0), Color::Black));
+
+    // A larger K makes the same advantage more decisive.
+    let (soft, sharp) = (WinProbability::new(0.5), WinProbability::new(2.0));
+    assert!(soft.of(200.0) < sharp.of(200.0));
+    assert!((soft.centipawns(soft.of(200.0)) - 200.0).abs() < 1e-6);
+    // Certain results still map to a finite score.
+    assert!(sharp.centipawns(1.0).is_finite() && sharp.centipawns(1.0) > 0.0);
+}
+
+#[test]
+fn test_pawn_structure() {
+    let table = PawnTable::default();
+    let healthy = Board::from_str("4k3/8/8/8/8/8/PPP5/4K3 w - - 0 1").unwrap();
+    let broken = Board::from_str("4k3/8/8/8/8/P7/P1P5/4K3 w - - 0 1").unwrap();
+
+    // All pawns are passed, but a2 is blocked by a3; the broken pawns are also doubled,
+    // isolated and split into two islands.
+    assert_eq!(15.0, pawns::evaluate(Color::White, &healthy, &table));
+    assert_eq!(
+        17.5 - 12.0 - 3.0 * 12.0 - 6.0,
+        pawns::evaluate(Color::White, &broken, &table)
+    );
+    assert_eq!(-15.0, pawns::evaluate(Color::Black, &healthy, &table));
+}
+
+#[test]
+fn test_pawn_table_reset() {
+    let mut table = PawnTable::default();
+    let broken = Board::from_str("4k3/8/8/8/8/P7/P1P5/4K3 w - - 0 1").unwrap();
+    let before = pawns::evaluate(Color::White, &broken, &table);
+
+    // The cached entry was scored with the old weights and must not be reused.
+    table.reset(PawnWeights {
+        doubled: 0.0,
+        ..PawnWeights::default()
+    });
+    assert_eq!(
+        before + 12.0,
+        pawns::evaluate(Color::White, &broken, &table)
+    );
+}
+
+#[test]
+fn test_king_safety() {
+    let weights = EvalWeights::default();
+    let sheltered = Board::from_str("4k3/8/8/8/8/8/5PPP/6K1 w - - 0 1").unwrap();
+
+    // The white king has three shield pawns (3 * 10), the black king two open files
+    // and a semi-open one (2 * -20 - 10).
+    assert_eq!(
+        80.0,
+        king_safety::evaluate(Color::White, &sheltered, &weights, 1.0)
+    );
+    assert_eq!(
+        -40.0,
+        king_safety::evaluate(Color::Black, &sheltered, &weights, 0.5)
+    );
+
+    // The knight attacks g2 in the king zone: one attacker (-8) and one attack (-4).
+    let attacked = Board::from_str("4k3/8/8/8/5n2/8/5PPP/6K1 w - - 0 1").unwrap();
+    assert_eq!(
+        68.0,
+        king_safety::evaluate(Color::White, &attacked, &weights, 1.0)
+    );
+}
+
+#[test]
+fn test_mobility() {
+    let weights = EvalWeights::default();
+    let evaluate =
+        |fen: &str| mobility::evaluate(Color::White, &Board::from_str(fen).unwrap(), &weights);
+
+    // The knight in the corner reaches 2 squares, the centralised one 8 (4 each).
+    assert_eq!(-24.0, evaluate("4k3/8/8/3n4/8/8/8/N3K3 w - - 0 1"));
+    // The pawn on d2 covers c3 and e3.
+    assert_eq!(-16.0, evaluate("4k3/8/8/3n4/8/8/3P4/N3K3 w - - 0 1"));
+    // Behind its own pawn the bishop has no square, on the open diagonal 7 (5 each).
+    assert_eq!(0.0, evaluate("4k3/8/8/8/8/8/1P6/B3K3 w - - 0 1"));
+    assert_eq!(35.0, evaluate("4k3/8/8/8/8/8/8/B3K3 w - - 0 1"));
 }
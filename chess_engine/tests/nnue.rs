@@ -0,0 +1,234 @@
+#![cfg(feature = "nnue")]
+
+use std::{str::FromStr, sync::Arc};
+
+use chess::{Board, Color, Game, Piece};
+use chess_engine::{
+    evaluator::{ChessEvaluation, ChessEvaluator},
+    nnue::{Accumulator, Network, NnueEvaluator},
+    state::GameWrapper,
+    tree_search::{ChessGame, ChessNnueGame},
+};
+use mcts::GameState;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use search_tree::{mini_max::mini_max, GameEvaluator};
+
+const L1: usize = 40;
+const L2: usize = 8;
+const FEATURES: usize = 64 * 641;
+
+/// Unquantised copy of a random network, evaluated the straightforward way.
+struct Reference {
+    feature_biases: Vec<i32>,
+    feature_weights: Vec<i32>,
+    hidden_biases: Vec<i32>,
+    hidden_weights: Vec<i32>,
+    output_bias: i32,
+    output_weights: Vec<i32>,
+}
+
+impl Reference {
+    fn random(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut values = |count: usize, low: i32, high: i32| -> Vec<i32> {
+            (0..count).map(|_| rng.gen_range(low..=high)).collect()
+        };
+
+        Self {
+            feature_biases: values(L1, -20, 80),
+            feature_weights: values(FEATURES * L1, -40, 40),
+            hidden_biases: values(L2, -2000, 2000),
+            hidden_weights: values(L2 * 2 * L1, -128, 127),
+            output_bias: values(1, -500, 500)[0],
+            output_weights: values(L2, -128, 127),
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = b"NNUEHKP1".to_vec();
+        bytes.extend((L1 as u32).to_le_bytes());
+        bytes.extend((L2 as u32).to_le_bytes());
+        bytes.extend(
+            self.feature_biases
+                .iter()
+                .flat_map(|&v| (v as i16).to_le_bytes()),
+        );
+        bytes.extend(
+            self.feature_weights
+                .iter()
+                .flat_map(|&v| (v as i16).to_le_bytes()),
+        );
+        bytes.extend(self.hidden_biases.iter().flat_map(|&v| v.to_le_bytes()));
+        bytes.extend(self.hidden_weights.iter().map(|&v| v as i8 as u8));
+        bytes.extend(self.output_bias.to_le_bytes());
+        bytes.extend(self.output_weights.iter().map(|&v| v as i8 as u8));
+        bytes
+    }
+
+    fn accumulate(&self, board: &Board, perspective: Color) -> Vec<i32> {
+        let orient = |index: usize| match perspective {
+            Color::White => index,
+            Color::Black => index ^ 56,
+        };
+        let king = orient(board.king_square(perspective).to_index());
+        let mut values = self.feature_biases.clone();
+
+        for square in *board.combined() {
+            let piece = board.piece_on(square).unwrap();
+            if piece == Piece::King {
+                continue;
+            }
+
+            let color = board.color_on(square).unwrap();
+            let kind = piece.to_index() * 2 + if color == perspective { 0 } else { 1 };
+            let feature = king * 641 + kind * 64 + orient(square.to_index()) + 1;
+
+            for (i, value) in values.iter_mut().enumerate() {
+                *value += self.feature_weights[feature * L1 + i];
+            }
+        }
+
+        values
+    }
+
+    fn evaluate(&self, board: &Board) -> i32 {
+        let side_to_move = board.side_to_move();
+        let input: Vec<i32> = [side_to_move, !side_to_move]
+            .iter()
+            .flat_map(|&perspective| self.accumulate(board, perspective))
+            .map(|value| value.clamp(0, 127))
+            .collect();
+
+        let mut output = self.output_bias;
+        for j in 0..L2 {
+            let mut sum = self.hidden_biases[j];
+            for (i, value) in input.iter().enumerate() {
+                sum += value * self.hidden_weights[j * 2 * L1 + i];
+            }
+            output += (sum >> 6).clamp(0, 127) * self.output_weights[j];
+        }
+
+        output / 16
+    }
+}
+
+fn network(reference: &Reference) -> Arc<Network> {
+    Arc::new(Network::from_bytes(&reference.to_bytes()).unwrap())
+}
+
+#[test]
+fn test_load_network() {
+    let reference = Reference::random(1);
+    let bytes = reference.to_bytes();
+    let path = std::env::temp_dir().join("chess_engine_test.nnue");
+    std::fs::write(&path, &bytes).unwrap();
+
+    let network = Network::load(&path).unwrap();
+    assert_eq!((L1, L2), network.dimensions());
+
+    assert!(Network::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    assert!(Network::from_bytes(&[bytes.as_slice(), &[0]].concat()).is_err());
+    assert!(Network::from_bytes(b"NNUEHKP0").is_err());
+}
+
+#[test]
+fn test_matches_reference() {
+    let reference = Reference::random(2);
+    let network = network(&reference);
+
+    for fen in [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b KQkq - 0 1",
+    ] {
+        let board = Board::from_str(fen).unwrap();
+        assert_eq!(
+            reference.evaluate(&board),
+            network.evaluate_board(&board),
+            "{}",
+            fen
+        );
+
+        let white = ChessEvaluator::new(Color::White).with_network(network.clone());
+        let black = ChessEvaluator::new(Color::Black).with_network(network.clone());
+        assert_eq!(white.evaluate_board(&board), -black.evaluate_board(&board));
+    }
+}
+
+#[test]
+fn test_incremental_updates_match_reference() {
+    let reference = Reference::random(3);
+    let network = network(&reference);
+    let evaluator = ChessEvaluator::new(Color::White).with_network(network.clone());
+    let mut rng = StdRng::seed_from_u64(4);
+
+    // Castling, en passant and promotions all show up in these games.
+    for fen in [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    ] {
+        for _ in 0..4 {
+            let start = Board::from_str(fen).unwrap();
+            let mut state =
+                GameWrapper::new(Game::new_with_board(start)).with_network(network.clone());
+
+            for _ in 0..80 {
+                let moves = state.available_moves();
+                if moves.is_empty() {
+                    break;
+                }
+
+                state.make_move(&moves[rng.gen_range(0..moves.len())]);
+
+                let board = *state.board();
+                let nnue = state.nnue().unwrap();
+                assert_eq!(&Accumulator::new(&network, &board), nnue.accumulator());
+                assert_eq!(
+                    reference.evaluate(&board),
+                    nnue.evaluate(board.side_to_move())
+                );
+
+                if state.outcome().is_none() {
+                    assert_eq!(
+                        ChessEvaluation::Evaluation(evaluator.evaluate_board(&board)),
+                        GameEvaluator::evaluate(&evaluator, &state, 0)
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_nnue_evaluator_replaces_chess_evaluator() {
+    let reference = Reference::random(5);
+    let network = network(&reference);
+    let board =
+        Board::from_str("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b KQkq - 0 1")
+            .unwrap();
+
+    for color in [Color::White, Color::Black] {
+        let nnue = NnueEvaluator::new(color, network.clone());
+        let chess = ChessEvaluator::new(color).with_network(network.clone());
+        assert_eq!(chess.evaluate_board(&board), nnue.evaluate_board(&board));
+    }
+    assert_eq!(
+        -reference.evaluate(&board) as f64,
+        NnueEvaluator::new(Color::White, network.clone()).evaluate_board(&board)
+    );
+
+    // Minimax finds the same evaluation with either evaluator.
+    let state = GameWrapper::new(Game::new_with_board(board)).with_network(network.clone());
+    let nnue = NnueEvaluator::new(Color::Black, network.clone());
+    let chess = ChessEvaluator::new(Color::Black).with_network(network);
+    assert_eq!(
+        mini_max::<ChessGame>(&chess, state.clone(), 2)
+            .unwrap()
+            .evaluation,
+        mini_max::<ChessNnueGame>(&nnue, state, 2)
+            .unwrap()
+            .evaluation
+    );
+}
@@ -0,0 +1,60 @@
+use std::str::FromStr;
+
+use chess::Board;
+use chess_engine::perft::{divide, perft};
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+fn board(fen: &str) -> Board {
+    Board::from_str(fen).unwrap()
+}
+
+/// Compares with the published counts for depths 1, 2, ...
+fn assert_perft(board: &Board, expected: &[u64]) {
+    for (depth, &nodes) in expected.iter().enumerate() {
+        assert_eq!(nodes, perft(board, depth + 1), "depth {}", depth + 1);
+    }
+}
+
+#[test]
+fn test_perft_startpos() {
+    assert_eq!(1, perft(&Board::default(), 0));
+    assert_perft(&Board::default(), &[20, 400, 8902, 197281, 4865609]);
+}
+
+#[test]
+fn test_perft_kiwipete() {
+    assert_perft(&board(KIWIPETE), &[48, 2039, 97862]);
+}
+
+#[test]
+fn test_perft_en_passant_and_pins() {
+    assert_perft(
+        &board("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1"),
+        &[14, 191, 2812, 43238],
+    );
+}
+
+#[test]
+fn test_perft_promotions_and_castling() {
+    assert_perft(
+        &board("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1"),
+        &[6, 264, 9467],
+    );
+    assert_perft(
+        &board("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8"),
+        &[44, 1486, 62379],
+    );
+}
+
+#[test]
+fn test_divide() {
+    let board = board(KIWIPETE);
+    let single = divide(&board, 2, 1);
+    let parallel = divide(&board, 2, 4);
+
+    assert_eq!(48, single.len());
+    assert_eq!(2039, single.iter().map(|(_, nodes)| nodes).sum::<u64>());
+    assert_eq!(single, parallel);
+    assert!(divide(&board, 0, 1).is_empty());
+}
@@ -0,0 +1,91 @@
+use std::str::FromStr;
+
+use chess::{Board, ChessMove, Game, MoveGen};
+use chess_engine::{
+    evaluator::{
+        params::EvalParams,
+        priors::{move_priors, move_score},
+    },
+    search::SearchConfig,
+    SearchAlgorithm, SearchPolicy, PUCT_EXPLORATION,
+};
+
+fn priors(fen: &str) -> Vec<(ChessMove, f64)> {
+    let board = Board::from_str(fen).unwrap();
+    let moves = MoveGen::new_legal(&board).collect::<Vec<_>>();
+    let priors = move_priors(&board, &moves, &EvalParams::default());
+
+    moves.into_iter().zip(priors).collect()
+}
+
+fn prior(priors: &[(ChessMove, f64)], mov: &str) -> f64 {
+    let mov = ChessMove::from_str(mov).unwrap();
+    priors.iter().find(|(m, _)| *m == mov).unwrap().1
+}
+
+#[test]
+fn test_priors_are_probabilities() {
+    let priors = priors("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    let sum = priors.iter().map(|(_, prior)| prior).sum::<f64>();
+
+    assert_eq!(20, priors.len());
+    assert!((sum - 1.0).abs() < 1e-9);
+    assert!(priors.iter().all(|(_, prior)| *prior > 0.0));
+    // The piece-square tables prefer the centre pawns over the rook pawns.
+    assert!(prior(&priors, "e2e4") > prior(&priors, "a2a3"));
+}
+
+#[test]
+fn test_priors_prefer_good_captures() {
+    // The knight on e4 can take the undefended queen on f6 with check, the queen on d1
+    // can take the pawn on d5 that the rook defends.
+    let priors = priors("3rk3/8/5q2/3p4/4N3/8/8/3QK3 w - - 0 1");
+    let best = priors
+        .iter()
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .unwrap()
+        .0;
+
+    assert_eq!(ChessMove::from_str("e4f6").unwrap(), best);
+    assert!(prior(&priors, "d1d2") > prior(&priors, "d1d5"));
+}
+
+#[test]
+fn test_move_score_terms() {
+    let params = EvalParams::default();
+    let board = Board::from_str("4k3/8/8/8/8/8/1p6/4K2R b K - 0 1").unwrap();
+    let score = |mov: &str| move_score(&board, ChessMove::from_str(mov).unwrap(), &params);
+
+    // Promoting to a queen is worth about the difference in material.
+    assert!(score("b2b1q") > 700.0);
+    assert!(score("b2b1q") > score("b2b1n"));
+
+    // The check outweighs the better square on the seventh rank.
+    let board = Board::from_str("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+    let score = |mov: &str| move_score(&board, ChessMove::from_str(mov).unwrap(), &params);
+    assert!(score("a1a8") > score("a1a7"));
+}
+
+#[test]
+fn test_mcts_puct() {
+    // Nxf6+ wins the queen and has the highest prior, see test_priors_prefer_good_captures.
+    let fen = "3rk3/8/5q2/3p4/4N3/8/8/3QK3 w - - 0 1";
+    let capture = ChessMove::from_str("e4f6").unwrap();
+
+    let config = SearchConfig::default()
+        .with_algorithm(SearchAlgorithm::Mcts(SearchPolicy::Puct {
+            exploration: PUCT_EXPLORATION,
+        }))
+        .with_playouts(10_000)
+        .with_threads(1);
+    let report = chess_engine::find_move(&Game::from_str(fen).unwrap(), &config);
+
+    let most_visited = report
+        .root_visits
+        .iter()
+        .max_by_key(|(_, visits)| *visits)
+        .unwrap()
+        .0;
+    assert_eq!(capture, most_visited);
+    assert_eq!(Some(capture), report.best_move);
+}
@@ -0,0 +1,120 @@
+use std::str::FromStr;
+
+use chess::{Board, ChessMove, Color, Game, MoveGen};
+use chess_engine::{
+    evaluator::{
+        rollout::{heavy_weight, rollout, LeafEvaluation, RolloutPolicy},
+        ChessEvaluation, ChessEvaluator,
+    },
+    mcts_handler::ChessMCTS,
+    search::SearchConfig,
+    state::GameWrapper,
+};
+use mcts::Evaluator;
+use rand::{rngs::StdRng, SeedableRng};
+
+fn evaluate_leaf(evaluator: &ChessEvaluator, fen: &str) -> ChessEvaluation {
+    let game = Game::from_str(fen).unwrap();
+    let moves = MoveGen::new_legal(&game.current_position()).collect::<Vec<_>>();
+
+    Evaluator::<ChessMCTS>::evaluate_new_state(evaluator, &GameWrapper::new(game), &moves, None).1
+}
+
+#[test]
+fn test_rollout_length() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let start = GameWrapper::new(Game::new());
+
+    for policy in [RolloutPolicy::Random, RolloutPolicy::Heavy] {
+        for _ in 0..20 {
+            let (end, plies) = rollout(start.position(), policy, 12, &mut rng);
+
+            assert!(plies <= 12);
+            assert!(plies == 12 || end.outcome().is_some());
+        }
+    }
+}
+
+#[test]
+fn test_rollout_from_finished_game() {
+    let mated =
+        Game::from_str("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3").unwrap();
+    let (end, plies) = rollout(
+        GameWrapper::new(mated).position(),
+        RolloutPolicy::Random,
+        10,
+        &mut StdRng::seed_from_u64(1),
+    );
+
+    assert_eq!(0, plies);
+    assert_eq!(Some(ChessEvaluation::Winner(Color::Black)), end.outcome());
+}
+
+#[test]
+fn test_heavy_weights() {
+    // White can trade queens on d5, give check with the knight on d6 or play quietly.
+    let board = Board::from_str("4k3/8/4p3/3q1p2/4N3/8/8/3QK3 w - - 0 1").unwrap();
+    let weight = |mov: &str| heavy_weight(&board, ChessMove::from_str(mov).unwrap());
+
+    let quiet = weight("e1e2");
+    assert!(weight("e4d6") > quiet, "knight check");
+    assert!(weight("d1d5") > weight("e4d6"), "winning the queen");
+    assert!(weight("e4d2") == quiet);
+}
+
+#[test]
+fn test_hybrid_without_rollout_is_static() {
+    let fen = "rnb1k1nr/p4ppp/p1p5/8/8/2P2P1P/P5P1/1Rb1K1NR b kq - 1 14";
+    let hybrid = LeafEvaluation::Hybrid {
+        plies: 10,
+        rollout_weight: 0.0,
+    };
+
+    let plain = evaluate_leaf(&ChessEvaluator::new(Color::Black), fen);
+    let blended = evaluate_leaf(
+        &ChessEvaluator::new(Color::Black).with_leaf_evaluation(hybrid),
+        fen,
+    );
+
+    assert_eq!(plain, blended);
+}
+
+#[test]
+fn test_rollout_keeps_game_outcome() {
+    let mated = "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3";
+    let evaluator =
+        ChessEvaluator::new(Color::White).with_leaf_evaluation(LeafEvaluation::Random { plies: 8 });
+
+    assert_eq!(
+        ChessEvaluation::Winner(Color::Black),
+        evaluate_leaf(&evaluator, mated)
+    );
+}
+
+#[test]
+fn test_named_leaf_evaluation() {
+    assert_eq!(
+        Some(LeafEvaluation::Heavy { plies: 30 }),
+        LeafEvaluation::named("Heavy", 30)
+    );
+    assert_eq!(
+        Some(LeafEvaluation::Random { plies: 12 }),
+        LeafEvaluation::named("random", 30).map(|leaf| leaf.with_plies(12))
+    );
+    assert_eq!(None, LeafEvaluation::named("quiescence", 30));
+}
+
+#[test]
+fn test_mcts_heavy_rollouts() {
+    // The black queen on h4 is en prise to the queen on h5 and nothing guards it.
+    let game =
+        Game::from_str("rnb1kbnr/pppp1ppp/8/4p2Q/4P2q/8/PPPP1PPP/RNB1KBNR w KQkq - 2 3").unwrap();
+
+    let config = SearchConfig::default()
+        .with_leaf_evaluation(LeafEvaluation::Heavy { plies: 20 })
+        .with_playouts(2_000)
+        .with_threads(1);
+    let report = chess_engine::find_move(&game, &config);
+
+    assert_eq!(Some(ChessMove::from_str("h5h4").unwrap()), report.best_move);
+}
@@ -0,0 +1,52 @@
+use std::str::FromStr;
+
+use chess::{Board, ChessMove};
+use chess_engine::see::{see, see_ge};
+
+fn see_of(fen: &str, mov: &str) -> i32 {
+    let board = Board::from_str(fen).unwrap();
+    see(&board, ChessMove::from_str(mov).unwrap())
+}
+
+#[test]
+fn test_see_undefended() {
+    assert_eq!(
+        100,
+        see_of("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "e1e5")
+    );
+}
+
+#[test]
+fn test_see_losing_capture() {
+    // Nxe5 Nxe5 Rxe5 Bxe5 Qxe5 Qxe5: white is better off not recapturing with the rook
+    // and stays a knight for a pawn down.
+    assert_eq!(
+        100 - 320,
+        see_of(
+            "1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1",
+            "d3e5"
+        )
+    );
+}
+
+#[test]
+fn test_see_x_ray() {
+    // The second rook recaptures through the first one.
+    assert_eq!(100, see_of("4k3/4r3/8/4p3/8/8/4R3/4R2K w - - 0 1", "e2e5"));
+    assert_eq!(-400, see_of("4k3/4r3/8/4p3/8/8/4R3/7K w - - 0 1", "e2e5"));
+}
+
+#[test]
+fn test_see_king_cannot_recapture_defended_piece() {
+    assert_eq!(100, see_of("8/8/3k4/4p3/8/8/4R3/4R2K w - - 0 1", "e2e5"));
+    assert!(see_ge(
+        &Board::from_str("8/8/3k4/4p3/8/8/4R3/4R2K w - - 0 1").unwrap(),
+        ChessMove::from_str("e2e5").unwrap(),
+        0
+    ));
+}
+
+#[test]
+fn test_see_en_passant() {
+    assert_eq!(100, see_of("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6"));
+}
@@ -0,0 +1,67 @@
+use std::str::FromStr;
+
+use chess::{ChessMove, Game, MoveGen};
+use chess_engine::{
+    search::{MoveSource, SearchConfig, SearchReport},
+    session::SearchSession,
+    SearchAlgorithm,
+};
+
+#[test]
+fn test_mcts_session_reuses_tree() {
+    let game = Game::new();
+    let config = SearchConfig::default().with_playouts(5_000).with_threads(1);
+    let mut session = SearchSession::new(config);
+
+    let first = session.find_move(&game);
+    assert_eq!(0, first.reused_visits);
+
+    // Searching the same position again continues the tree.
+    let second = session.find_move(&game);
+    assert!(second.reused_visits > 0);
+    assert_eq!(MoveSource::Search, second.source);
+    let visits = |report: &SearchReport| report.root_visits.iter().map(|(_, v)| v).sum::<u64>();
+    assert!(visits(&second) > visits(&first));
+}
+
+#[test]
+fn test_mcts_session_new_tree_after_move() {
+    let mut game = Game::new();
+    let config = SearchConfig::default().with_playouts(2_000).with_threads(1);
+    let mut session = SearchSession::new(config);
+
+    let first = session.find_move(&game);
+    game.make_move(first.best_move.unwrap());
+
+    let second = session.find_move(&game);
+    assert_eq!(0, second.reused_visits);
+    let mov = second.best_move.unwrap();
+    assert!(MoveGen::new_legal(&game.current_position()).any(|legal| legal == mov));
+}
+
+#[test]
+fn test_mcts_session_rebuilds_for_other_game() {
+    let config = SearchConfig::default().with_playouts(2_000).with_threads(1);
+    let mut session = SearchSession::new(config);
+
+    session.find_move(&Game::new());
+
+    let game = Game::from_str("r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5Q2/PPPP1PPP/RNB1K1NR w KQkq - 4 4")
+        .unwrap();
+    let report = session.find_move(&game);
+    assert_eq!(0, report.reused_visits);
+}
+
+#[test]
+fn test_session_mini_max() {
+    let game = Game::from_str("r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5Q2/PPPP1PPP/RNB1K1NR w KQkq - 4 4")
+        .unwrap();
+
+    let config = SearchConfig::default()
+        .with_algorithm(SearchAlgorithm::MiniMax { depth: 2 })
+        .with_threads(1);
+    let report = SearchSession::new(config).find_move(&game);
+
+    assert_eq!(Some(ChessMove::from_str("f3f7").unwrap()), report.best_move);
+    assert_eq!(0, report.reused_visits);
+}
@@ -0,0 +1,158 @@
+use std::{collections::HashSet, str::FromStr};
+
+use chess::{ChessMove, Color, Game, MoveGen};
+use chess_engine::{
+    evaluator::ChessEvaluation,
+    state::{DrawReason, GameWrapper},
+};
+use mcts::GameState;
+
+fn play(wrapper: &mut GameWrapper, moves: &[&str]) {
+    for mov in moves {
+        wrapper.make_move(&ChessMove::from_str(mov).unwrap());
+    }
+}
+
+fn wrapper(fen: &str) -> GameWrapper {
+    GameWrapper::new(Game::from_str(fen).unwrap())
+}
+
+#[test]
+fn test_threefold_repetition() {
+    let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+    let mut wrapper = GameWrapper::default();
+
+    play(&mut wrapper, &shuffle);
+    play(&mut wrapper, &shuffle[..3]);
+    assert!(!wrapper.is_terminal());
+
+    play(&mut wrapper, &shuffle[3..]);
+    assert_eq!(
+        Some(ChessEvaluation::Draw(DrawReason::ThreefoldRepetition)),
+        wrapper.outcome()
+    );
+    assert_eq!(None, wrapper.get_winner());
+}
+
+#[test]
+fn test_repetition_history_of_wrapped_game() {
+    let mut game = Game::new();
+    for mov in ["g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1"] {
+        assert!(game.make_move(ChessMove::from_str(mov).unwrap()));
+    }
+
+    let mut wrapper = GameWrapper::new(game);
+    assert!(!wrapper.is_terminal());
+
+    play(&mut wrapper, &["f6g8"]);
+    assert_eq!(Some(DrawReason::ThreefoldRepetition), wrapper.draw_reason());
+}
+
+#[test]
+fn test_irreversible_move_resets_repetitions() {
+    let mut wrapper = GameWrapper::default();
+    play(&mut wrapper, &["g1f3", "g8f6", "f3g1", "f6g8", "e2e4"]);
+    play(&mut wrapper, &["g8f6", "g1f3", "f6g8", "f3g1"]);
+    play(&mut wrapper, &["g8f6", "g1f3", "f6g8"]);
+
+    assert_eq!(7, wrapper.halfmove_clock());
+    assert!(!wrapper.is_terminal());
+}
+
+#[test]
+fn test_castling_rights_change_keeps_halfmove_clock() {
+    let mut wrapper = wrapper("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+
+    // Both rook moves give up a castling right, but neither is a capture or pawn move.
+    play(&mut wrapper, &["e1f1", "a8a7", "h1h2"]);
+    assert_eq!(3, wrapper.halfmove_clock());
+    assert!(!wrapper.board().castle_rights(Color::Black).has_queenside());
+}
+
+#[test]
+fn test_fifty_move_rule() {
+    let mut wrapper = wrapper("r2qk2r/8/2n1bn2/8/8/2N1BN2/8/R2QK2R w - - 0 1");
+    let mut seen = HashSet::new();
+    seen.insert(wrapper.board().get_hash());
+
+    for ply in 1..=100 {
+        assert!(!wrapper.is_terminal(), "terminal before ply {}", ply);
+
+        let board = *wrapper.board();
+        let mov = MoveGen::new_legal(&board)
+            .find(|mov| {
+                let next = board.make_move_new(*mov);
+                board.piece_on(mov.get_dest()).is_none()
+                    && next.checkers().popcnt() == 0
+                    && !seen.contains(&next.get_hash())
+            })
+            .unwrap();
+
+        wrapper.make_move(&mov);
+        seen.insert(wrapper.board().get_hash());
+        assert_eq!(ply, wrapper.halfmove_clock());
+    }
+
+    assert_eq!(
+        Some(ChessEvaluation::Draw(DrawReason::FiftyMoveRule)),
+        wrapper.outcome()
+    );
+}
+
+#[test]
+fn test_checkmate() {
+    let mut wrapper = wrapper("7k/8/6K1/8/8/8/8/R7 w - - 0 1");
+    assert_eq!(None, wrapper.outcome());
+
+    play(&mut wrapper, &["a1a8"]);
+    assert_eq!(
+        Some(ChessEvaluation::Winner(Color::White)),
+        wrapper.outcome()
+    );
+    assert_eq!(Some(Color::White), wrapper.get_winner());
+}
+
+#[test]
+fn test_insufficient_material() {
+    let draw = Some(DrawReason::InsufficientMaterial);
+
+    assert_eq!(draw, wrapper("8/8/4k3/8/8/4K3/8/8 w - - 0 1").draw_reason());
+    assert_eq!(
+        draw,
+        wrapper("8/8/4k3/8/8/4KB2/8/8 w - - 0 1").draw_reason()
+    );
+    assert_eq!(
+        draw,
+        wrapper("8/8/4k3/8/8/4KN2/8/8 b - - 0 1").draw_reason()
+    );
+    // Both bishops on dark squares.
+    assert_eq!(
+        draw,
+        wrapper("8/8/4kb2/8/8/4K3/8/2B5 w - - 0 1").draw_reason()
+    );
+
+    // Bishops on opposite colours, two knights, or a pawn can still mate.
+    assert_eq!(
+        None,
+        wrapper("8/8/4k1b1/8/8/4K3/8/2B5 w - - 0 1").draw_reason()
+    );
+    assert_eq!(
+        None,
+        wrapper("8/8/4k3/8/8/4KNN1/8/8 w - - 0 1").draw_reason()
+    );
+    assert_eq!(
+        None,
+        wrapper("8/8/4k3/8/8/4K3/4P3/8 w - - 0 1").draw_reason()
+    );
+}
+
+#[test]
+fn test_stalemate() {
+    let wrapper = wrapper("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1");
+
+    assert_eq!(
+        Some(ChessEvaluation::Draw(DrawReason::Stalemate)),
+        wrapper.outcome()
+    );
+    assert!(wrapper.available_moves().is_empty());
+}
@@ -0,0 +1,164 @@
+use std::{collections::HashMap, path::Path, str::FromStr, sync::Arc};
+
+use chess::{Board, ChessMove, Color, Game, MoveGen};
+use chess_engine::{
+    evaluator::{ChessEvaluation, ChessEvaluator},
+    mcts_handler::ChessMCTS,
+    state::{DrawReason, GameWrapper},
+    tablebase::{self, Tablebase, TablebaseConfig, Wdl},
+};
+use mcts::Evaluator;
+
+/// Tables that only know the positions they are given.
+#[derive(Default)]
+struct FakeTablebase(HashMap<u64, (Wdl, i32)>);
+
+impl FakeTablebase {
+    fn insert(&mut self, board: &Board, wdl: Wdl, dtz: i32) {
+        self.0.insert(board.get_hash(), (wdl, dtz));
+    }
+}
+
+impl Tablebase for FakeTablebase {
+    fn max_pieces(&self) -> u32 {
+        5
+    }
+
+    fn probe_wdl(&self, board: &Board) -> Option<Wdl> {
+        Some(
+            self.0
+                .get(&board.get_hash())
+                .map_or(Wdl::Draw, |entry| entry.0),
+        )
+    }
+
+    fn probe_dtz(&self, board: &Board) -> Option<i32> {
+        Some(self.0.get(&board.get_hash()).map_or(0, |entry| entry.1))
+    }
+}
+
+fn after(board: &Board, mov: &str) -> Board {
+    board.make_move_new(ChessMove::from_str(mov).unwrap())
+}
+
+#[test]
+fn test_tablebase_evaluation() {
+    let board = Board::from_str("8/8/8/4k3/8/8/8/3QK3 w - - 0 1").unwrap();
+    let mut tables = FakeTablebase::default();
+    tables.insert(&board, Wdl::Win, 20);
+
+    let evaluator = ChessEvaluator::new(Color::Black).with_tablebase(Arc::new(tables));
+    let evaluate = |board: Board| {
+        let moves = MoveGen::new_legal(&board).collect::<Vec<_>>();
+        let state = GameWrapper::new(Game::new_with_board(board));
+        Evaluator::<ChessMCTS>::evaluate_new_state(&evaluator, &state, &moves, None).1
+    };
+
+    assert_eq!(ChessEvaluation::Winner(Color::White), evaluate(board));
+    assert_eq!(
+        ChessEvaluation::Draw(DrawReason::Tablebase),
+        evaluate(Board::from_str("8/8/8/4k3/8/8/8/3RK3 w - - 0 1").unwrap())
+    );
+
+    // Too many pieces for the tables.
+    let crowded = Board::from_str("8/pp6/8/4k3/8/8/PP6/3QK3 w - - 0 1").unwrap();
+    assert!(matches!(evaluate(crowded), ChessEvaluation::Evaluation(_)));
+}
+
+#[test]
+fn test_tablebase_best_move() {
+    let board = Board::from_str("8/8/8/4k3/8/8/8/3QK3 w - - 0 1").unwrap();
+    let mut tables = FakeTablebase::default();
+    for mov in MoveGen::new_legal(&board) {
+        tables.insert(&board.make_move_new(mov), Wdl::Loss, -30);
+    }
+    tables.insert(&after(&board, "d1d4"), Wdl::Loss, -12);
+    tables.insert(&after(&board, "d1g4"), Wdl::Loss, -8);
+
+    assert_eq!(
+        Some((ChessMove::from_str("d1g4").unwrap(), Wdl::Win)),
+        tablebase::best_move(&tables, &board, 0)
+    );
+
+    // With 95 plies on the clock, no win fits before the fifty-move rule.
+    assert_eq!(
+        Some((ChessMove::from_str("d1g4").unwrap(), Wdl::CursedWin)),
+        tablebase::best_move(&tables, &board, 95)
+    );
+
+    // Losing, the longest way to the next zeroing move is best.
+    let losing = Board::from_str("8/8/8/4k3/8/8/8/3QK3 b - - 0 1").unwrap();
+    let mut tables = FakeTablebase::default();
+    for mov in MoveGen::new_legal(&losing) {
+        tables.insert(&losing.make_move_new(mov), Wdl::Win, 10);
+    }
+    tables.insert(&after(&losing, "e5f5"), Wdl::Win, 14);
+
+    assert_eq!(
+        Some((ChessMove::from_str("e5f5").unwrap(), Wdl::Loss)),
+        tablebase::best_move(&tables, &losing, 0)
+    );
+}
+
+#[test]
+fn test_tablebase_config() {
+    assert!(TablebaseConfig::default().open().unwrap().is_none());
+
+    let directory = std::env::temp_dir().join("chess_engine_syzygy");
+    std::fs::create_dir_all(&directory).unwrap();
+    let config = TablebaseConfig {
+        path: Some(directory.clone().into_os_string()),
+        ..Default::default()
+    };
+
+    let table = directory.join("KQvK.rtbw");
+    let _ = std::fs::remove_file(&table);
+    assert!(config.open().unwrap().is_none());
+
+    std::fs::write(&table, b"").unwrap();
+    assert_eq!(vec![table.clone()], config.table_files().unwrap());
+
+    std::fs::remove_file(&table).unwrap();
+}
+
+#[test]
+#[ignore = "needs the KQvK and KRvK Syzygy tables in tests/syzygy"]
+fn test_syzygy_tables() {
+    let config = TablebaseConfig {
+        path: Some(
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("tests/syzygy")
+                .into(),
+        ),
+        ..Default::default()
+    };
+    let tables = config.open().unwrap().unwrap();
+    let board = |fen: &str| Board::from_str(fen).unwrap();
+
+    let kqk = board("8/8/8/4k3/8/8/8/3QK3 w - - 0 1");
+    assert_eq!(Some(Wdl::Win), tables.probe_wdl(&kqk));
+    assert!(tables.probe_dtz(&kqk).unwrap() > 0);
+    assert_eq!(
+        Some(ChessEvaluation::Winner(Color::White)),
+        tablebase::evaluate(tables.as_ref(), &kqk)
+    );
+
+    let (mov, wdl) = tablebase::best_move(tables.as_ref(), &kqk, 0).unwrap();
+    assert_eq!(Wdl::Win, wdl);
+    assert_eq!(Some(Wdl::Loss), tables.probe_wdl(&kqk.make_move_new(mov)));
+
+    let krk = board("8/8/8/4k3/8/8/8/R3K3 b - - 0 1");
+    assert_eq!(Some(Wdl::Loss), tables.probe_wdl(&krk));
+    assert!(tables.probe_dtz(&krk).unwrap() < 0);
+
+    // Black takes the undefended rook.
+    let hanging = board("8/8/8/8/8/8/k7/R3K3 b - - 0 1");
+    assert_eq!(Some(Wdl::Draw), tables.probe_wdl(&hanging));
+    assert_eq!(Some(0), tables.probe_dtz(&hanging));
+
+    // Not in the tables.
+    assert_eq!(
+        None,
+        tables.probe_wdl(&board("8/8/8/4k3/8/8/8/2BNK3 w - - 0 1"))
+    );
+}
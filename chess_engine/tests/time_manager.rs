@@ -0,0 +1,136 @@
+use std::{str::FromStr, time::Duration};
+
+use chess::{ChessMove, Color, Game};
+use chess_engine::{
+    search::SearchConfig,
+    time_manager::{Clock, TimeManager},
+    uci::GoCommand,
+    SearchAlgorithm,
+};
+
+fn clock(remaining: u64, increment: u64, movestogo: Option<u32>) -> Clock {
+    Clock {
+        remaining: Duration::from_secs(remaining),
+        increment: Duration::from_secs(increment),
+        movestogo,
+    }
+}
+
+fn visits(moves: &[(&str, u64)]) -> Vec<(ChessMove, u64)> {
+    moves
+        .iter()
+        .map(|(mov, visits)| (ChessMove::from_str(mov).unwrap(), *visits))
+        .collect()
+}
+
+fn millis(millis: u64) -> Duration {
+    Duration::from_millis(millis)
+}
+
+#[test]
+fn test_limits() {
+    let time = TimeManager::new(&clock(60, 0, None));
+    assert_eq!(millis(2000), time.soft_limit());
+    assert_eq!(millis(6000), time.hard_limit());
+
+    let time = TimeManager::new(&clock(60, 1, Some(20)));
+    assert_eq!(millis(3750), time.soft_limit());
+    assert_eq!(millis(11250), time.hard_limit());
+
+    // The last move before the time control may use all but the reserve.
+    let time = TimeManager::new(&clock(10, 0, Some(1)));
+    assert_eq!(millis(9950), time.soft_limit());
+    assert_eq!(millis(9950), time.hard_limit());
+}
+
+#[test]
+fn test_stops_at_limits() {
+    let root = visits(&[("e2e4", 500), ("d2d4", 490)]);
+
+    let mut time = TimeManager::new(&clock(60, 0, None));
+    assert!(!time.should_stop(millis(1000), &root, Some(20.0)));
+    assert!(time.should_stop(millis(2000), &root, Some(20.0)));
+
+    let mut time = TimeManager::new(&clock(60, 0, None));
+    assert!(time.should_stop(millis(6000), &[], None));
+}
+
+#[test]
+fn test_extends_on_unstable_best_move() {
+    let mut time = TimeManager::new(&clock(60, 0, None));
+
+    time.should_stop(millis(100), &visits(&[("e2e4", 50), ("d2d4", 40)]), None);
+    assert_eq!(millis(2000), time.target());
+
+    time.should_stop(millis(200), &visits(&[("e2e4", 60), ("d2d4", 70)]), None);
+    assert_eq!(millis(2800), time.target());
+
+    // Never beyond the hard limit.
+    for i in 0..10 {
+        let root = match i % 2 {
+            0 => visits(&[("e2e4", 100), ("d2d4", 90)]),
+            _ => visits(&[("e2e4", 90), ("d2d4", 100)]),
+        };
+        time.should_stop(millis(300), &root, None);
+    }
+    assert_eq!(time.hard_limit(), time.target());
+}
+
+#[test]
+fn test_extends_on_score_drop() {
+    let root = visits(&[("e2e4", 500), ("d2d4", 490)]);
+    let mut time = TimeManager::new(&clock(60, 0, None));
+
+    // Scores are only trusted once the search settled.
+    time.should_stop(millis(100), &root, Some(100.0));
+    time.should_stop(millis(500), &root, Some(50.0));
+    time.should_stop(millis(600), &root, Some(25.0));
+    assert_eq!(millis(2000), time.target());
+
+    time.should_stop(millis(700), &root, Some(15.0));
+    assert_eq!(millis(4000), time.target());
+    assert!(!time.should_stop(millis(2500), &root, Some(15.0)));
+}
+
+#[test]
+fn test_stops_early_on_dominant_move() {
+    let mut time = TimeManager::new(&clock(60, 0, None));
+
+    // Too early to judge, and later still close enough to be overtaken.
+    assert!(!time.should_stop(millis(100), &visits(&[("e2e4", 200), ("d2d4", 1)]), None));
+    assert!(!time.should_stop(millis(1000), &visits(&[("e2e4", 1000), ("d2d4", 10)]), None));
+
+    assert!(time.should_stop(millis(1500), &visits(&[("e2e4", 3000), ("d2d4", 10)]), None));
+}
+
+#[test]
+fn test_uci_clock() {
+    let go = GoCommand::parse(&["wtime", "60000", "btime", "30000", "binc", "1000"]);
+
+    assert_eq!(Some(clock(30, 1, None)), go.limits(Color::Black).clock);
+    assert_eq!(Some(clock(60, 0, None)), go.limits(Color::White).clock);
+    assert_eq!(
+        None,
+        GoCommand::parse(&["infinite"]).limits(Color::White).clock
+    );
+}
+
+#[test]
+fn test_mini_max_stops_at_hard_limit() {
+    // Far too deep to finish; the search must still end within the hard limit.
+    let clock = clock(3, 0, None);
+    let hard_limit = TimeManager::new(&clock).hard_limit();
+    let config = SearchConfig::default()
+        .with_algorithm(SearchAlgorithm::MiniMax { depth: 12 })
+        .with_clock(clock);
+
+    let report = chess_engine::find_move(&Game::new(), &config);
+
+    assert!(report.best_move.is_some());
+    assert!(
+        report.elapsed < hard_limit + millis(100),
+        "took {:?}, hard limit {:?}",
+        report.elapsed,
+        hard_limit
+    );
+}
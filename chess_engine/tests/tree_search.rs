@@ -0,0 +1,22 @@
+use std::str::FromStr;
+
+use chess::{ChessMove, Game};
+use chess_engine::{
+    search::{MoveSource, SearchConfig},
+    SearchAlgorithm,
+};
+
+#[test]
+fn test_mini_max_finds_mate() {
+    let game = Game::from_str("r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5Q2/PPPP1PPP/RNB1K1NR w KQkq - 4 4")
+        .unwrap();
+
+    let config = SearchConfig::default()
+        .with_algorithm(SearchAlgorithm::MiniMax { depth: 2 })
+        .with_threads(4);
+    let report = chess_engine::find_move(&game, &config);
+
+    assert_eq!(Some(ChessMove::from_str("f3f7").unwrap()), report.best_move);
+    assert_eq!(vec![ChessMove::from_str("f3f7").unwrap()], report.pv);
+    assert_eq!(MoveSource::Search, report.source);
+}
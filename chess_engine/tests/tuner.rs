@@ -0,0 +1,115 @@
+use std::sync::Arc;
+
+use chess::{Board, Color, MoveGen};
+use chess_engine::{
+    evaluator::{params::EvalParams, ChessEvaluator},
+    tuner::{find_k, mean_squared_error, parse_epd_line, sigmoid, tune, TrainingPosition},
+};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+/// Positions from random games, labelled with what `params` predict for them with `k`,
+/// so that `params` and `k` fit them exactly.
+fn synthetic_positions(params: &EvalParams, k: f64) -> Vec<TrainingPosition> {
+    let evaluator = ChessEvaluator::with_params(Color::White, Arc::new(params.clone()));
+    let mut rng = StdRng::seed_from_u64(3);
+
+    (0..60)
+        .filter_map(|_| {
+            let mut board = Board::default();
+            for _ in 0..rng.gen_range(20..80) {
+                let moves = MoveGen::new_legal(&board).collect::<Vec<_>>();
+                board = board.make_move_new(*moves.choose(&mut rng)?);
+            }
+
+            let result = sigmoid(evaluator.evaluate_board(&board), k);
+            Some(TrainingPosition { board, result })
+        })
+        .collect()
+}
+
+#[test]
+fn test_parse_epd_results() {
+    let win = parse_epd_line("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - c9 \"1-0\";")
+        .unwrap();
+    let draw = parse_epd_line("8/8/4k3/8/8/4K3/8/8 w - - [0.5]").unwrap();
+    let loss = parse_epd_line("8/8/4k3/8/8/4K3/8/7q w - - 0 1 0-1").unwrap();
+
+    assert_eq!(1.0, win.result);
+    assert_eq!(0.5, draw.result);
+    assert_eq!(0.0, loss.result);
+    assert!(parse_epd_line("8/8/4k3/8/8/4K3/8/8 w - -").is_none());
+}
+
+#[test]
+fn test_params_round_trip() {
+    let mut params = EvalParams::default();
+    params.piece_values[1] = 333.0;
+    params.weights.open_file = -25.0;
+
+    let vector = params.to_vector();
+    assert_eq!(5 + 2 * 6 * 64 + 4 + 8 + 1 + 11, vector.len());
+    assert_eq!(params, EvalParams::from_vector(&vector));
+
+    for file in ["chess_engine_params.toml", "chess_engine_params.json"] {
+        let path = std::env::temp_dir().join(file);
+        params.save(&path).unwrap();
+        assert_eq!(params, EvalParams::load(&path).unwrap());
+    }
+}
+
+#[test]
+fn test_params_partial_file() {
+    let params = EvalParams::from_toml(
+        "piece_values = [100, 300, 300, 500, 900]\n\n[weights]\nopen_file = -30\n",
+    )
+    .unwrap();
+
+    assert_eq!([100.0, 300.0, 300.0, 500.0, 900.0], params.piece_values);
+    assert_eq!(-30.0, params.weights.open_file);
+    assert_eq!(EvalParams::default().mid_tables, params.mid_tables);
+    assert_eq!(
+        EvalParams::default().weights.pawn_shield,
+        params.weights.pawn_shield
+    );
+}
+
+#[test]
+fn test_find_k() {
+    let params = EvalParams::default();
+    let positions = synthetic_positions(&params, 1.5);
+
+    let k = find_k(&positions, &params);
+    assert!((k - 1.5).abs() < 0.01, "k = {}", k);
+}
+
+#[test]
+fn test_tune_finds_known_optimum() {
+    let mut target = EvalParams::default();
+    target.piece_values[1] += 50.0;
+    let positions = synthetic_positions(&target, 1.0);
+
+    // Only the piece values are tuned.
+    let start = EvalParams::default();
+    let steps = EvalParams::steps(5.0)
+        .into_iter()
+        .enumerate()
+        .map(|(i, step)| if i < 5 { step } else { 0.0 })
+        .collect::<Vec<_>>();
+    let tuned = tune(&positions, &start, 1.0, &steps, 20, |_, _, _| {});
+
+    let before = mean_squared_error(&positions, &start, 1.0);
+    let after = mean_squared_error(&positions, &tuned, 1.0);
+    assert!(after < before / 10.0, "error {} after {}", after, before);
+    assert_eq!(target.piece_values, tuned.piece_values);
+    assert_eq!(start.mid_tables, tuned.mid_tables);
+}
+
+#[test]
+fn test_tuning_steps() {
+    let steps = EvalParams::from_vector(&EvalParams::steps(2.0));
+
+    assert_eq!([2.0; 5], steps.piece_values);
+    assert_eq!(2.0, steps.weights.open_file);
+    // The blocked passed pawn share lies between 0 and 1.
+    assert_eq!(0.02, steps.pawns.blocked_passed);
+}
@@ -0,0 +1,23 @@
+use std::str::FromStr;
+
+use chess::Board;
+use chess_engine::xboard;
+
+#[test]
+fn test_game_result() {
+    let result = |fen: &str| xboard::game_result(&Board::from_str(fen).unwrap());
+
+    assert_eq!(
+        Some("1/2-1/2 {Stalemate}"),
+        result("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1")
+    );
+    assert_eq!(
+        Some("1-0 {White mates}"),
+        result("R6k/8/6K1/8/8/8/8/8 b - - 0 1")
+    );
+    assert_eq!(
+        Some("0-1 {Black mates}"),
+        result("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3")
+    );
+    assert_eq!(None, result("7k/8/6K1/8/8/8/8/R7 w - - 0 1"));
+}
@@ -1,25 +1,39 @@
 use chess::{ChessMove, Color, Game};
-use licoricedev::{client::Lichess, errors::LichessError, models::board::BoardState};
-use std::{str::FromStr, sync::Arc};
+use chess_engine::{
+    search::{MoveSource, SearchConfig},
+    time_manager::Clock,
+};
+use licoricedev::{
+    client::Lichess,
+    errors::LichessError,
+    models::board::{BoardState, GameState},
+};
+use std::{str::FromStr, sync::Arc, time::Duration};
 use tokio_stream::StreamExt;
 
 pub struct LiGame {
     id: String,
     lichess: Arc<Lichess>,
+    config: Arc<SearchConfig>,
 }
 
 impl LiGame {
-    pub async fn new(lichess: Arc<Lichess>, game_id: String) -> Result<Self, LichessError> {
+    pub async fn new(
+        lichess: Arc<Lichess>,
+        game_id: String,
+        config: Arc<SearchConfig>,
+    ) -> Result<Self, LichessError> {
         Ok(LiGame {
             id: game_id,
             lichess,
+            config,
         })
     }
 
     pub async fn start_game_loop(&self) -> Result<(), LichessError> {
         let mut stream = self.lichess.stream_bot_game_state(&self.id).await?;
 
-        let (bot_color, game) = match stream.try_next().await? {
+        let (bot_color, game, state) = match stream.try_next().await? {
             Some(board_state) => parse_initial_board_state(board_state),
             None => panic!("Could not get initial state!"),
         };
@@ -27,15 +41,15 @@ impl LiGame {
         println!("Moving as colour: {:?}", bot_color);
 
         if game.side_to_move() == bot_color {
-            self.search_move(&game, bot_color).await?;
+            self.search_move(&game, bot_color, &state).await?;
         }
 
         while let Some(board_state) = stream.next().await {
             match board_state {
                 Ok(board_state) => {
-                    if let Some(game) = parse_board_state(board_state) {
+                    if let Some((game, state)) = parse_board_state(board_state) {
                         if game.side_to_move() == bot_color {
-                            if let Err(e) = self.search_move(&game, bot_color).await {
+                            if let Err(e) = self.search_move(&game, bot_color, &state).await {
                                 println!("Could not play move, {:?}", e);
                             }
                         }
@@ -50,10 +64,39 @@ impl LiGame {
         Ok(())
     }
 
-    pub async fn search_move(&self, game: &Game, color: Color) -> Result<(), LichessError> {
+    pub async fn search_move(
+        &self,
+        game: &Game,
+        color: Color,
+        state: &GameState,
+    ) -> Result<(), LichessError> {
         println!("Start searching move for: {:?}", color);
-        let chess_move = chess_engine::find_move(game, 100_000, 14, color);
-        let uci_move = chess_move.to_string();
+        let config = self.config.as_ref().clone().with_clock(clock(state, color));
+        let report = chess_engine::find_move(game, &config);
+        let uci_move = match report.best_move {
+            Some(chess_move) => chess_move.to_string(),
+            None => {
+                println!("No move found");
+                return Ok(());
+            }
+        };
+
+        match report.source {
+            MoveSource::Book => println!("book move: {}", uci_move),
+            MoveSource::Tablebase => println!("tablebase move: {}", uci_move),
+            MoveSource::Search => println!(
+                "searched {} playouts in {:.1} s, score {:?}, pv {}",
+                report.playouts,
+                report.elapsed.as_secs_f64(),
+                report.score,
+                report
+                    .pv
+                    .iter()
+                    .map(|mov| mov.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ),
+        }
 
         println!("make move: {}", uci_move);
         self.lichess
@@ -63,7 +106,21 @@ impl LiGame {
     }
 }
 
-fn parse_board_state(board: BoardState) -> Option<Game> {
+/// Clock of `color`, lichess sends the times in milliseconds.
+fn clock(state: &GameState, color: Color) -> Clock {
+    let (remaining, increment) = match color {
+        Color::White => (state.wtime, state.winc),
+        Color::Black => (state.btime, state.binc),
+    };
+
+    Clock {
+        remaining: Duration::from_millis(remaining as u64),
+        increment: Duration::from_millis(increment as u64),
+        movestogo: None,
+    }
+}
+
+fn parse_board_state(board: BoardState) -> Option<(Game, GameState)> {
     let state = match board {
         BoardState::GameFull(full) => full.state,
         BoardState::GameState(state) => state,
@@ -79,10 +136,10 @@ fn parse_board_state(board: BoardState) -> Option<Game> {
         }
     }
 
-    Some(game)
+    Some((game, state))
 }
 
-fn parse_initial_board_state(board_state: BoardState) -> (Color, Game) {
+fn parse_initial_board_state(board_state: BoardState) -> (Color, Game, GameState) {
     let full = match board_state {
         BoardState::GameFull(full) => full,
         BoardState::GameState(_) => unreachable!(),
@@ -127,5 +184,5 @@ fn parse_initial_board_state(board_state: BoardState) -> (Color, Game) {
         }
     }
 
-    (color, game)
+    (color, game, full.state)
 }
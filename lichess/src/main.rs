@@ -1,4 +1,8 @@
 use crate::world::LiWorld;
+use chess_engine::{
+    book::OpeningBook, evaluator::params::EvalParams, search::SearchConfig,
+    tablebase::TablebaseConfig, DEFAULT_PLAYOUTS,
+};
 use dotenv::dotenv;
 use licoricedev::client::Lichess;
 use std::sync::Arc;
@@ -14,8 +18,60 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
     let lichess = Arc::new(Lichess::new(String::from(std::env::var("LICHESS_TOKEN")?)));
 
+    // Optional weight set for the evaluator, TOML or JSON.
+    let params = match std::env::var("EVAL_PARAMS") {
+        Ok(path) => {
+            println!("Loading evaluation weights: {}", path);
+            EvalParams::load(&path)?
+        }
+        Err(_) => EvalParams::default(),
+    };
+
+    let mut config = SearchConfig::default().with_params(Arc::new(params));
+
+    // Search limits: the clock of each game, and at most a fixed number of playouts so
+    // correspondence games don't think for days. Searches run on every core by default.
+    let playouts = match std::env::var("SEARCH_PLAYOUTS") {
+        Ok(playouts) => playouts.parse()?,
+        Err(_) => DEFAULT_PLAYOUTS,
+    };
+    config = config.with_playouts(playouts);
+    if let Ok(threads) = std::env::var("SEARCH_THREADS") {
+        config = config.with_threads(threads.parse()?);
+    }
+
+    // Optional Polyglot opening book.
+    if let Ok(path) = std::env::var("OPENING_BOOK") {
+        println!("Loading opening book: {}", path);
+        config = config.with_book(Arc::new(OpeningBook::load(&path)?));
+    }
+
+    // Optional Syzygy tables, the search runs without them when they can't be used.
+    let mut tablebase_config = TablebaseConfig {
+        path: std::env::var_os("SYZYGY_PATH"),
+        ..Default::default()
+    };
+    if let Ok(max_pieces) = std::env::var("SYZYGY_MAX_PIECES") {
+        tablebase_config.max_pieces = max_pieces.parse()?;
+    }
+    let tablebase = tablebase_config.open().unwrap_or_else(|e| {
+        println!("Not using tablebases: {}", e);
+        None
+    });
+    if let Some(tablebase) = tablebase {
+        config = config.with_tablebase(tablebase);
+    }
+
+    // Optional NNUE network, used instead of the handcrafted evaluation.
+    #[cfg(feature = "nnue")]
+    if let Ok(path) = std::env::var("NNUE_FILE") {
+        println!("Loading network: {}", path);
+        let network = chess_engine::nnue::Network::load(&path)?;
+        config = config.with_network(Arc::new(network));
+    }
+
     println!("Creating new world");
-    let mut world = LiWorld::new(lichess);
+    let mut world = LiWorld::new(lichess, Arc::new(config));
 
     println!("Start listening");
     world.listen().await?;
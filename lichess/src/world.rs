@@ -1,4 +1,5 @@
 use crate::ligame::LiGame;
+use chess_engine::search::SearchConfig;
 use licoricedev::{client::Lichess, errors::LichessError, models::board::Event};
 use std::{collections::HashMap, sync::Arc};
 use tokio::task::JoinHandle;
@@ -7,12 +8,14 @@ use tokio_stream::StreamExt;
 pub struct LiWorld {
     pub current_games: HashMap<String, JoinHandle<()>>,
     lichess: Arc<Lichess>,
+    config: Arc<SearchConfig>,
 }
 
 impl LiWorld {
-    pub fn new(lichess: Arc<Lichess>) -> Self {
+    pub fn new(lichess: Arc<Lichess>, config: Arc<SearchConfig>) -> Self {
         Self {
             lichess,
+            config,
             current_games: Default::default(),
         }
     }
@@ -31,11 +34,14 @@ impl LiWorld {
                 Event::Challenge { challenge } => {
                     println!("Received challenge: {:?}", challenge);
                     if challenge.variant.name.to_lowercase() != "standard" {
-                        println!("Rejected challenge, not standard but: {}", challenge.variant.name);
+                        println!(
+                            "Rejected challenge, not standard but: {}",
+                            challenge.variant.name
+                        );
                         self.reject_challenge(challenge.id).await?;
                         continue;
-                    } else if challenge.speed.to_lowercase() != "correspondence" {
-                        println!("Rejected challenge, not correspondence but: {}", challenge.speed);
+                    } else if challenge.speed.to_lowercase() == "ultrabullet" {
+                        println!("Rejected challenge, too fast: {}", challenge.speed);
                         self.reject_challenge(challenge.id).await?;
                         continue;
                     } else {
@@ -52,11 +58,12 @@ impl LiWorld {
     pub fn start_game(&mut self, game_id: String) {
         println!("Starting game: {}", game_id);
         let lichess = self.lichess.clone();
+        let config = self.config.clone();
 
         let game_id_2 = game_id.clone();
 
         let handle = tokio::spawn(async move {
-            let li_game = LiGame::new(lichess, game_id.clone())
+            let li_game = LiGame::new(lichess, game_id.clone(), config)
                 .await
                 .expect("Could not get lichess game");
             match li_game.start_game_loop().await {
@@ -0,0 +1,121 @@
+use rayon::iter::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
+
+use crate::{
+    mini_max::MiniMaxResult, Evaluation, Evaluator, GameEvaluator, GameNode, Move, Node, SearchGame,
+};
+
+struct BeamNode<N: SearchGame> {
+    node: Node<N>,
+    found_move: Option<Move<N>>,
+    parent: Option<usize>,
+    /// Static evaluation of the node, replaced by the best evaluation of its children
+    /// once they are backed up.
+    evaluation: Option<Evaluation<N>>,
+    /// The child `evaluation` was backed up from, and its value for the player to move.
+    best: Option<(usize, f64)>,
+}
+
+/// Minimax on the tree that keeps, at every ply, only the `width` positions with the
+/// best static evaluation for the player who moved into them. Positions kept whose
+/// children were all dropped count with their static evaluation.
+pub fn beam_search<N: SearchGame>(
+    searcher: &Evaluator<N>,
+    node: Node<N>,
+    depth: usize,
+    width: usize,
+) -> Option<MiniMaxResult<N>>
+where
+    Move<N>: Send + Sync,
+    Node<N>: Send + Sync,
+    Evaluation<N>: Send + Sync,
+    Evaluator<N>: Send + Sync,
+{
+    if depth == 0 || width == 0 {
+        return None;
+    }
+
+    if node.is_terminal().is_some() {
+        return None;
+    }
+
+    let mut tree = vec![BeamNode::<N> {
+        node,
+        found_move: None,
+        parent: None,
+        evaluation: None,
+        best: None,
+    }];
+    let mut frontier = vec![0];
+
+    for ply in 1..=depth {
+        let kept = beam::<N>(searcher, &tree, &frontier, ply, width);
+
+        frontier = (tree.len()..tree.len() + kept.len()).collect();
+        tree.extend(kept);
+    }
+
+    // Children always come after their parent, so going backwards every node has its
+    // final evaluation by the time it is backed up.
+    for index in (1..tree.len()).rev() {
+        let parent = tree[index].parent.expect("only the root has no parent");
+        let evaluation = tree[index].evaluation.take().expect("a static evaluation");
+        let interpreted =
+            searcher.interpret_for_player(&evaluation, tree[parent].node.current_player());
+
+        if tree[parent].best.is_none_or(|(_, best)| interpreted > best) {
+            tree[parent].best = Some((index, interpreted));
+            tree[parent].evaluation = Some(evaluation);
+        }
+    }
+
+    let (best, _) = tree[0].best?;
+    Some(MiniMaxResult {
+        found_move: tree[best].found_move.take().expect("a move into the child"),
+        evaluation: tree[0].evaluation.take().expect("a backed up evaluation"),
+    })
+}
+
+/// The `width` children of the `frontier` nodes ranked best by their static evaluation.
+fn beam<N: SearchGame>(
+    searcher: &Evaluator<N>,
+    tree: &[BeamNode<N>],
+    frontier: &[usize],
+    ply: usize,
+    width: usize,
+) -> Vec<BeamNode<N>>
+where
+    Move<N>: Send + Sync,
+    Node<N>: Send + Sync,
+    Evaluation<N>: Send + Sync,
+    Evaluator<N>: Send + Sync,
+{
+    let mut children = frontier
+        .par_iter()
+        .flat_map(|&parent| {
+            let node = &tree[parent].node;
+            let moves = match node.is_terminal() {
+                Some(_) => Vec::new(),
+                None => node.legal_moves(),
+            };
+
+            moves.into_par_iter().map(move |mov| {
+                let child_node = node.make_move(mov.clone());
+                let eval = searcher.evaluate(&child_node, ply);
+                let interpreted = searcher.interpret_for_player(&eval, node.current_player());
+                let child = BeamNode {
+                    node: child_node,
+                    found_move: Some(mov),
+                    parent: Some(parent),
+                    evaluation: Some(eval),
+                    best: None,
+                };
+                (child, interpreted)
+            })
+        })
+        .collect::<Vec<_>>();
+
+    children.sort_by(|a, b| b.1.partial_cmp(&a.1).expect("Could not compare"));
+    children.truncate(width);
+
+    children.into_iter().map(|(child, _)| child).collect()
+}
@@ -0,0 +1,168 @@
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+use crate::{
+    mini_max::MiniMaxResult, Evaluation, Evaluator, GameEvaluator, GameNode, Move, Node,
+    Player, SearchGame,
+};
+
+struct TreeNode<N: SearchGame> {
+    node: Node<N>,
+    found_move: Option<Move<N>>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    depth: usize,
+    /// Evaluation of the leaf at the end of the principal variation below this node.
+    evaluation: Evaluation<N>,
+    /// `evaluation` interpreted for the player to move at the root.
+    value: f64,
+    /// Whether there is a non-terminal leaf below this node that can still be expanded.
+    open: bool,
+}
+
+/// Best-first minimax in the spirit of SSS*: the tree is grown by expanding the leaf
+/// at the end of the current principal variation until `node_budget` nodes have been
+/// evaluated, or the whole tree is. When the principal variation ends in a terminal
+/// node, the most promising line that can still be expanded is followed instead.
+/// Expanding a node evaluates all of its children, so a node whose children don't fit
+/// in the budget left ends the search.
+pub fn best_first_search<N: SearchGame>(
+    searcher: &Evaluator<N>,
+    node: Node<N>,
+    node_budget: usize,
+) -> Option<MiniMaxResult<N>>
+where
+    Move<N>: Send + Sync,
+    Node<N>: Send + Sync,
+    Player<N>: Clone + PartialEq + Send + Sync,
+    Evaluation<N>: Send + Sync,
+    Evaluator<N>: Send + Sync,
+{
+    if node_budget == 0 {
+        return None;
+    }
+
+    if node.is_terminal().is_some() {
+        return None;
+    }
+
+    let root_player = node.current_player();
+    let evaluation = searcher.evaluate(&node, 0);
+    let value = searcher.interpret_for_player(&evaluation, node.current_player());
+    let mut tree = vec![TreeNode::<N> {
+        node,
+        found_move: None,
+        parent: None,
+        children: Vec::new(),
+        depth: 0,
+        evaluation,
+        value,
+        open: true,
+    }];
+    let mut evaluated = 1;
+
+    while evaluated < node_budget && tree[0].open {
+        let mut leaf = 0;
+        while let Some(best) = best_child(&tree, leaf, &root_player, true) {
+            leaf = best;
+        }
+
+        let moves = tree[leaf].node.legal_moves();
+        if evaluated + moves.len() > node_budget {
+            break;
+        }
+        evaluated += moves.len();
+
+        for child in expand::<N>(searcher, &tree, leaf, moves, &root_player) {
+            let index = tree.len();
+            tree.push(child);
+            tree[leaf].children.push(index);
+        }
+
+        let mut current = Some(leaf);
+        while let Some(index) = current {
+            if let Some(best) = best_child(&tree, index, &root_player, false) {
+                tree[index].evaluation = tree[best].evaluation.clone();
+                tree[index].value = tree[best].value;
+            }
+            tree[index].open = tree[index].children.iter().any(|&child| tree[child].open);
+            current = tree[index].parent;
+        }
+    }
+
+    let best = best_child(&tree, 0, &root_player, false)?;
+    let best = tree.swap_remove(best);
+
+    best.found_move.map(|found_move| MiniMaxResult {
+        found_move,
+        evaluation: best.evaluation,
+    })
+}
+
+/// The child that decides the value of `index`: the maximum for the root player, and
+/// the minimum for its opponent. With `only_open`, the best child that is still open.
+fn best_child<N: SearchGame>(
+    tree: &[TreeNode<N>],
+    index: usize,
+    root_player: &Player<N>,
+    only_open: bool,
+) -> Option<usize>
+where
+    Player<N>: PartialEq,
+{
+    let maximize = tree[index].node.current_player() == *root_player;
+
+    tree[index]
+        .children
+        .iter()
+        .copied()
+        .filter(|&child| !only_open || tree[child].open)
+        .max_by(|&a, &b| {
+            let order = tree[a]
+                .value
+                .partial_cmp(&tree[b].value)
+                .expect("Could not compare");
+
+            if maximize {
+                order
+            } else {
+                order.reverse()
+            }
+        })
+}
+
+fn expand<N: SearchGame>(
+    searcher: &Evaluator<N>,
+    tree: &[TreeNode<N>],
+    leaf: usize,
+    moves: Vec<Move<N>>,
+    root_player: &Player<N>,
+) -> Vec<TreeNode<N>>
+where
+    Move<N>: Send + Sync,
+    Node<N>: Send + Sync,
+    Player<N>: Clone + PartialEq + Send + Sync,
+    Evaluation<N>: Send + Sync,
+    Evaluator<N>: Send + Sync,
+{
+    let parent = &tree[leaf];
+
+    moves
+        .into_par_iter()
+        .map(|mov| {
+            let node = parent.node.make_move(mov.clone());
+            let evaluation = searcher.evaluate(&node, parent.depth + 1);
+            let value = searcher.interpret_for_player(&evaluation, root_player.clone());
+            let open = node.is_terminal().is_none();
+            TreeNode {
+                node,
+                found_move: Some(mov),
+                parent: Some(leaf),
+                children: Vec::new(),
+                depth: parent.depth + 1,
+                evaluation,
+                value,
+                open,
+            }
+        })
+        .collect()
+}
@@ -1,4 +1,7 @@
+pub mod beam;
+pub mod best_first;
 pub mod mini_max;
+pub mod opponent_model;
 // pub mod alpha_beta;
 
 pub type Node<S> = <S as SearchGame>::Node;
@@ -1,3 +1,5 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
 use crate::{Evaluation, Evaluator, GameEvaluator, GameNode, Move, Node, SearchGame};
@@ -18,6 +20,24 @@ where
     Node<N>: Send + Sync,
     Evaluation<N>: Send + Sync,
     Evaluator<N>: Send + Sync,
+{
+    mini_max_until(searcher, node, depth, &|| false)
+}
+
+/// [`mini_max`] that gives up as soon as `should_stop` returns true, which is checked at
+/// every node. An interrupted search has no result.
+pub fn mini_max_until<N: SearchGame, F>(
+    searcher: &Evaluator<N>,
+    node: Node<N>,
+    depth: usize,
+    should_stop: &F,
+) -> Option<MiniMaxResult<N>>
+where
+    Move<N>: Send + Sync,
+    Node<N>: Send + Sync,
+    Evaluation<N>: Send + Sync,
+    Evaluator<N>: Send + Sync,
+    F: Fn() -> bool + Sync,
 {
     if depth == 0 {
         return None;
@@ -27,11 +47,21 @@ where
         return None;
     }
 
-    node.legal_moves()
+    let interrupted = AtomicBool::new(false);
+    let should_stop = || {
+        let stop = should_stop();
+        if stop {
+            interrupted.store(true, Ordering::Relaxed);
+        }
+        stop
+    };
+
+    let result = node
+        .legal_moves()
         .into_par_iter()
         .map(|mov| {
             let child_node = node.make_move(mov.clone());
-            let eval = max_min_phase::<N>(searcher, child_node, 1, depth);
+            let eval = max_min_phase_until::<N, _, _>(searcher, child_node, 1, depth, &should_stop);
             let interpreted = searcher.interpret_for_player(&eval, node.current_player());
             (eval, interpreted, mov)
         })
@@ -39,31 +69,59 @@ where
         .map(|(eval, _, mov)| MiniMaxResult {
             found_move: mov,
             evaluation: eval,
-        })
+        });
+
+    match interrupted.load(Ordering::Relaxed) {
+        true => None,
+        false => result,
+    }
 }
 
-fn max_min_phase<N: SearchGame>(
-    searcher: &Evaluator<N>,
+pub(crate) fn max_min_phase<N: SearchGame, E>(
+    searcher: &E,
     node: N::Node,
     depth: usize,
     max_depth: usize,
-) -> Evaluation<N>
+) -> E::Evaluation
 where
     Move<N>: Send + Sync,
     Node<N>: Send + Sync,
-    Evaluation<N>: Send + Sync,
-    Evaluator<N>: Send + Sync,
+    E: GameEvaluator<N> + Send + Sync,
+    E::Evaluation: Send + Sync,
+{
+    max_min_phase_until::<N, E, _>(searcher, node, depth, max_depth, &|| false)
+}
+
+fn max_min_phase_until<N: SearchGame, E, F>(
+    searcher: &E,
+    node: N::Node,
+    depth: usize,
+    max_depth: usize,
+    should_stop: &F,
+) -> E::Evaluation
+where
+    Move<N>: Send + Sync,
+    Node<N>: Send + Sync,
+    E: GameEvaluator<N> + Send + Sync,
+    E::Evaluation: Send + Sync,
+    F: Fn() -> bool + Sync,
 {
     use rayon::prelude::*;
 
-    if depth == max_depth || node.is_terminal().is_some() {
+    if depth == max_depth || node.is_terminal().is_some() || should_stop() {
         return searcher.evaluate(&node, depth);
     }
 
     node.legal_moves()
         .into_par_iter()
         .map(|mov| {
-            let eval = max_min_phase::<N>(searcher, node.make_move(mov), depth + 1, max_depth);
+            let eval = max_min_phase_until::<N, E, F>(
+                searcher,
+                node.make_move(mov),
+                depth + 1,
+                max_depth,
+                should_stop,
+            );
             let interpreted = searcher.interpret_for_player(&eval, node.current_player());
             (eval, interpreted)
         })
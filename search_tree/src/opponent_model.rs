@@ -0,0 +1,172 @@
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+use crate::{
+    mini_max::max_min_phase, Evaluation, Evaluator, GameEvaluator, GameNode, Move, Node,
+    Player, SearchGame,
+};
+
+/// How the opponent is expected to pick its moves: by searching `depth` plies with
+/// its own `evaluator`, which does not have to be ours.
+pub struct OpponentModel<'a, E> {
+    pub evaluator: &'a E,
+    pub depth: usize,
+}
+
+pub struct OpponentModelResult<N: SearchGame> {
+    pub found_move: Move<N>,
+    /// Evaluation at the end of the line the opponent model predicts.
+    pub evaluation: Evaluation<N>,
+    /// Evaluation of the move against an opponent playing our best replies.
+    pub guaranteed: Evaluation<N>,
+}
+
+/// Searches `depth` plies assuming the opponent replies as `opponent` predicts.
+///
+/// Only moves whose plain minimax value is within `safety_margin` of the best minimax
+/// value are considered, so a wrong model can cost at most `safety_margin`.
+pub fn opponent_model_search<N: SearchGame, O>(
+    searcher: &Evaluator<N>,
+    opponent: &OpponentModel<O>,
+    node: Node<N>,
+    depth: usize,
+    safety_margin: f64,
+) -> Option<OpponentModelResult<N>>
+where
+    Move<N>: Send + Sync,
+    Node<N>: Send + Sync,
+    Player<N>: Clone + PartialEq + Send + Sync,
+    Evaluation<N>: Send + Sync,
+    Evaluator<N>: Send + Sync,
+    O: GameEvaluator<N> + Send + Sync,
+    O::Evaluation: Send + Sync,
+{
+    if depth == 0 || opponent.depth == 0 {
+        return None;
+    }
+
+    if node.is_terminal().is_some() {
+        return None;
+    }
+
+    let us = node.current_player();
+
+    let candidates = node
+        .legal_moves()
+        .into_par_iter()
+        .map(|mov| {
+            let guaranteed = max_min_phase::<N, _>(searcher, node.make_move(mov.clone()), 1, depth);
+            let predicted = model_phase::<N, O>(
+                searcher,
+                opponent,
+                node.make_move(mov.clone()),
+                &us,
+                1,
+                depth,
+            );
+            (mov, guaranteed, predicted)
+        })
+        .collect::<Vec<_>>();
+
+    let best_guaranteed = candidates
+        .iter()
+        .map(|(_, guaranteed, _)| searcher.interpret_for_player(guaranteed, us.clone()))
+        .fold(f64::NEG_INFINITY, f64::max);
+
+    candidates
+        .into_iter()
+        .filter(|(_, guaranteed, _)| {
+            searcher.interpret_for_player(guaranteed, us.clone()) >= best_guaranteed - safety_margin
+        })
+        .map(|(mov, guaranteed, predicted)| {
+            let interpreted = searcher.interpret_for_player(&predicted, us.clone());
+            (mov, guaranteed, predicted, interpreted)
+        })
+        .max_by(|a, b| a.3.partial_cmp(&b.3).expect("Could not compare"))
+        .map(|(mov, guaranteed, predicted, _)| OpponentModelResult {
+            found_move: mov,
+            evaluation: predicted,
+            guaranteed,
+        })
+}
+
+fn model_phase<N: SearchGame, O>(
+    searcher: &Evaluator<N>,
+    opponent: &OpponentModel<O>,
+    node: N::Node,
+    us: &Player<N>,
+    depth: usize,
+    max_depth: usize,
+) -> Evaluation<N>
+where
+    Move<N>: Send + Sync,
+    Node<N>: Send + Sync,
+    Player<N>: Clone + PartialEq + Send + Sync,
+    Evaluation<N>: Send + Sync,
+    Evaluator<N>: Send + Sync,
+    O: GameEvaluator<N> + Send + Sync,
+    O::Evaluation: Send + Sync,
+{
+    if depth == max_depth || node.is_terminal().is_some() {
+        return searcher.evaluate(&node, depth);
+    }
+
+    if node.current_player() != *us {
+        let reply = predict_reply::<N, O>(opponent, &node);
+        return model_phase::<N, O>(
+            searcher,
+            opponent,
+            node.make_move(reply),
+            us,
+            depth + 1,
+            max_depth,
+        );
+    }
+
+    node.legal_moves()
+        .into_par_iter()
+        .map(|mov| {
+            let eval = model_phase::<N, O>(
+                searcher,
+                opponent,
+                node.make_move(mov),
+                us,
+                depth + 1,
+                max_depth,
+            );
+            let interpreted = searcher.interpret_for_player(&eval, us.clone());
+            (eval, interpreted)
+        })
+        .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+        .unwrap()
+        .0
+}
+
+/// The reply the opponent is expected to play, searched with its own evaluator.
+fn predict_reply<N: SearchGame, O>(
+    opponent: &OpponentModel<O>,
+    node: &N::Node,
+) -> Move<N>
+where
+    Move<N>: Send + Sync,
+    Node<N>: Send + Sync,
+    O: GameEvaluator<N> + Send + Sync,
+    O::Evaluation: Send + Sync,
+{
+    node.legal_moves()
+        .into_par_iter()
+        .map(|mov| {
+            let eval = max_min_phase::<N, O>(
+                opponent.evaluator,
+                node.make_move(mov.clone()),
+                1,
+                opponent.depth,
+            );
+            let interpreted = opponent
+                .evaluator
+                .interpret_for_player(&eval, node.current_player());
+            (mov, interpreted)
+        })
+        .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+        .unwrap()
+        .0
+}
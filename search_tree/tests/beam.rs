@@ -0,0 +1,51 @@
+mod common;
+
+use common::{random_tree, ToyEvaluator, ToyGame, ToyNode};
+use search_tree::{beam::beam_search, mini_max::mini_max};
+
+#[test]
+fn test_wide_beam_is_minimax() {
+    for seed in 0..10 {
+        let (tree, values) = random_tree(3, 4, seed);
+        let searcher = ToyEvaluator::new(values);
+
+        let expected = mini_max::<ToyGame>(&searcher, ToyNode::root(tree.clone()), 4).unwrap();
+        let result = beam_search::<ToyGame>(&searcher, ToyNode::root(tree), 4, 81).unwrap();
+
+        assert_eq!(expected.found_move, result.found_move);
+        assert_eq!(expected.evaluation, result.evaluation);
+    }
+}
+
+#[test]
+fn test_beam_width_per_ply() {
+    let (tree, values) = random_tree(4, 4, 0);
+    let searcher = ToyEvaluator::new(values);
+
+    assert!(beam_search::<ToyGame>(&searcher, ToyNode::root(tree), 4, 2).is_some());
+    // The 4 moves from the root, then the 4 moves of the 2 positions kept at each of
+    // the 3 plies after.
+    assert_eq!(4 + 3 * 2 * 4, searcher.evaluations());
+}
+
+#[test]
+fn test_beam_keeps_best_of_ply() {
+    // Both replies to move 1 look better for the opponent than the replies to move 2,
+    // so a beam of 2 keeps only those at the second ply, and move 2 is left with its
+    // static evaluation. Keeping 2 replies per position would back up 3 for move 2.
+    let tree = vec![
+        vec![1, 2],
+        vec![3, 4],
+        vec![5, 6],
+        vec![],
+        vec![],
+        vec![],
+        vec![],
+    ];
+    let values = vec![0.0, 2.0, 1.0, -4.0, -3.0, 3.0, 4.0];
+    let searcher = ToyEvaluator::new(values);
+
+    let result = beam_search::<ToyGame>(&searcher, ToyNode::root(tree), 2, 2).unwrap();
+    assert_eq!(2, result.found_move);
+    assert_eq!(1.0, result.evaluation);
+}
@@ -0,0 +1,45 @@
+mod common;
+
+use common::{random_tree, ToyEvaluator, ToyGame, ToyNode};
+use search_tree::{best_first::best_first_search, mini_max::mini_max};
+
+#[test]
+fn test_full_budget_is_minimax() {
+    for seed in 0..10 {
+        let (tree, values) = random_tree(3, 4, seed);
+        let budget = tree.len();
+        let searcher = ToyEvaluator::new(values);
+
+        let expected = mini_max::<ToyGame>(&searcher, ToyNode::root(tree.clone()), 4).unwrap();
+        let result = best_first_search::<ToyGame>(&searcher, ToyNode::root(tree), budget).unwrap();
+
+        assert_eq!(expected.found_move, result.found_move);
+        assert_eq!(expected.evaluation, result.evaluation);
+    }
+}
+
+#[test]
+fn test_search_past_terminal_principal_variation() {
+    // Move 1 ends the game at 5, move 2 looks worse but the opponent can't hold it
+    // below 8.
+    let tree = vec![vec![1, 2], vec![], vec![3, 4], vec![], vec![]];
+    let values = vec![0.0, 5.0, 3.0, 10.0, 8.0];
+    let searcher = ToyEvaluator::new(values);
+
+    let result = best_first_search::<ToyGame>(&searcher, ToyNode::root(tree), 10).unwrap();
+    assert_eq!(2, result.found_move);
+    assert_eq!(8.0, result.evaluation);
+}
+
+#[test]
+fn test_node_budget() {
+    let (tree, values) = random_tree(4, 3, 0);
+
+    for budget in 1..tree.len() {
+        let searcher = ToyEvaluator::new(values.clone());
+        let result = best_first_search::<ToyGame>(&searcher, ToyNode::root(tree.clone()), budget);
+
+        assert!(searcher.evaluations() <= budget);
+        assert_eq!(budget > 4, result.is_some());
+    }
+}
@@ -0,0 +1,132 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use search_tree::{GameEvaluator, GameNode, SearchGame};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Side {
+    Max,
+    Min,
+}
+
+/// A game given by its tree: the moves of node `i` are `tree[i]`, the indices of the
+/// nodes they lead to. Nodes without moves are terminal.
+#[derive(Clone)]
+pub struct ToyNode {
+    tree: Arc<Vec<Vec<usize>>>,
+    index: usize,
+    player: Side,
+}
+
+impl ToyNode {
+    pub fn root(tree: Vec<Vec<usize>>) -> Self {
+        ToyNode {
+            tree: Arc::new(tree),
+            index: 0,
+            player: Side::Max,
+        }
+    }
+}
+
+impl GameNode for ToyNode {
+    type Move = usize;
+    type TerminalResult = ();
+    type Player = Side;
+
+    fn current_player(&self) -> Self::Player {
+        self.player
+    }
+
+    fn legal_moves(&self) -> Vec<Self::Move> {
+        self.tree[self.index].clone()
+    }
+
+    fn is_terminal(&self) -> Option<Self::TerminalResult> {
+        self.tree[self.index].is_empty().then_some(())
+    }
+
+    fn make_move(&self, m: Self::Move) -> Self {
+        ToyNode {
+            tree: self.tree.clone(),
+            index: m,
+            player: match self.player {
+                Side::Max => Side::Min,
+                Side::Min => Side::Max,
+            },
+        }
+    }
+}
+
+/// Values node `i` of the tree with `values[i]` for the maximizing side, counting the
+/// evaluations.
+pub struct ToyEvaluator {
+    values: Vec<f64>,
+    evaluations: AtomicUsize,
+}
+
+impl ToyEvaluator {
+    pub fn new(values: Vec<f64>) -> Self {
+        ToyEvaluator {
+            values,
+            evaluations: AtomicUsize::new(0),
+        }
+    }
+
+    // Not every test counts the evaluations.
+    #[allow(dead_code)]
+    pub fn evaluations(&self) -> usize {
+        self.evaluations.load(Ordering::Relaxed)
+    }
+}
+
+pub struct ToyGame;
+
+impl SearchGame for ToyGame {
+    type Node = ToyNode;
+    type Evaluator = ToyEvaluator;
+}
+
+impl GameEvaluator<ToyGame> for ToyEvaluator {
+    type Evaluation = f64;
+
+    fn evaluate(&self, node: &ToyNode, _depth: usize) -> Self::Evaluation {
+        self.evaluations.fetch_add(1, Ordering::Relaxed);
+        self.values[node.index]
+    }
+
+    fn interpret_for_player(&self, evaluation: &Self::Evaluation, player: Side) -> f64 {
+        match player {
+            Side::Max => *evaluation,
+            Side::Min => -*evaluation,
+        }
+    }
+}
+
+/// A complete tree of `depth` plies with `branching` moves per node, and random
+/// values for all of its nodes.
+pub fn random_tree(branching: usize, depth: usize, seed: u64) -> (Vec<Vec<usize>>, Vec<f64>) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut tree = vec![Vec::new()];
+    let mut ply = vec![0];
+
+    for _ in 0..depth {
+        let mut next = Vec::new();
+        for parent in ply {
+            for _ in 0..branching {
+                let child = tree.len();
+                tree[parent].push(child);
+                next.push(child);
+                tree.push(Vec::new());
+            }
+        }
+        ply = next;
+    }
+
+    let values = (0..tree.len())
+        .map(|_| rng.gen_range(-100.0..100.0))
+        .collect();
+    (tree, values)
+}
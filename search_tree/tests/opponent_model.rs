@@ -0,0 +1,95 @@
+mod common;
+
+use common::{random_tree, ToyEvaluator, ToyGame, ToyNode};
+use search_tree::{
+    mini_max::mini_max,
+    opponent_model::{opponent_model_search, OpponentModel},
+};
+
+/// Move 1 is safe, the opponent's best reply holds us to 1. After move 2 the opponent
+/// has to find reply 6, which leaves us at -5; reply 5 gives us 5.
+fn trap() -> (Vec<Vec<usize>>, Vec<f64>) {
+    let tree = vec![
+        vec![1, 2],
+        vec![3, 4],
+        vec![5, 6],
+        vec![],
+        vec![],
+        vec![],
+        vec![],
+    ];
+    let values = vec![0.0, 0.0, 0.0, 1.0, 2.0, 5.0, -5.0];
+
+    (tree, values)
+}
+
+/// An opponent that misjudges the trap and expects reply 5 to win.
+fn weak_opponent() -> ToyEvaluator {
+    ToyEvaluator::new(vec![0.0, 0.0, 0.0, 1.0, 2.0, -10.0, 3.0])
+}
+
+#[test]
+fn test_exploit_opponent_mistake() {
+    let (tree, values) = trap();
+    let searcher = ToyEvaluator::new(values);
+    let opponent = weak_opponent();
+    let model = OpponentModel {
+        evaluator: &opponent,
+        depth: 1,
+    };
+
+    assert_eq!(
+        1,
+        mini_max::<ToyGame>(&searcher, ToyNode::root(tree.clone()), 2)
+            .unwrap()
+            .found_move
+    );
+
+    let result =
+        opponent_model_search::<ToyGame, _>(&searcher, &model, ToyNode::root(tree), 2, 10.0)
+            .unwrap();
+    assert_eq!(2, result.found_move);
+    assert_eq!(5.0, result.evaluation);
+    assert_eq!(-5.0, result.guaranteed);
+}
+
+#[test]
+fn test_safety_margin_rejects_losing_line() {
+    let (tree, values) = trap();
+    let searcher = ToyEvaluator::new(values);
+    let opponent = weak_opponent();
+    let model = OpponentModel {
+        evaluator: &opponent,
+        depth: 1,
+    };
+
+    // Move 2 is 6 worse than move 1 against the best replies.
+    let result =
+        opponent_model_search::<ToyGame, _>(&searcher, &model, ToyNode::root(tree), 2, 5.0)
+            .unwrap();
+    assert_eq!(1, result.found_move);
+    assert_eq!(1.0, result.evaluation);
+    assert_eq!(1.0, result.guaranteed);
+}
+
+#[test]
+fn test_perfect_model_is_minimax() {
+    for seed in 0..10 {
+        let (tree, values) = random_tree(3, 4, seed);
+        let searcher = ToyEvaluator::new(values.clone());
+        let opponent = ToyEvaluator::new(values);
+        let model = OpponentModel {
+            evaluator: &opponent,
+            depth: 4,
+        };
+
+        let expected = mini_max::<ToyGame>(&searcher, ToyNode::root(tree.clone()), 4).unwrap();
+        let result =
+            opponent_model_search::<ToyGame, _>(&searcher, &model, ToyNode::root(tree), 4, 0.0)
+                .unwrap();
+
+        assert_eq!(expected.found_move, result.found_move);
+        assert_eq!(expected.evaluation, result.evaluation);
+        assert_eq!(expected.evaluation, result.guaranteed);
+    }
+}